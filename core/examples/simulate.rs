@@ -0,0 +1,86 @@
+//! Runs a batch of headless games with a simple bot and prints a summary.
+//!
+//!     cargo run -p feedback_rush_core --example simulate --release -- 1000 [hz]
+//!
+//! Each run is recorded, round-tripped through the replay format and played
+//! back to check the simulation is deterministic.
+
+use feedback_rush_core::{
+    builtin_modes, step, InputSource, OrbKind, Replay, TickInput, World, ARENA, TICK_RATE,
+    TICK_RATES,
+};
+
+// Caps a run so a lucky bot can't stall the batch (10 minutes of game time)
+const MAX_SECONDS: f32 = 60.0 * 10.0;
+
+/// Heads for the nearest orb that isn't a decoy and phases when a ghost
+/// gets close.
+struct Bot;
+
+impl InputSource for Bot {
+    fn next_input(&mut self, w: &World) -> Option<TickInput> {
+        let p = w.player.pos;
+        let target = w
+            .orbs
+            .iter()
+            .filter(|o| o.kind != OrbKind::Decoy)
+            .map(|o| o.pos)
+            .min_by(|a, b| a.distance_squared(p).total_cmp(&b.distance_squared(p)));
+        let dir = target.map(|t| (t - p).normalize_or_zero()).unwrap_or_default();
+        let want_phase = w
+            .ghosts
+            .iter()
+            .any(|g| g.current_pos().distance(p) < g.radius + w.player.radius + 24.0);
+        Some(TickInput { dir, want_phase })
+    }
+}
+
+fn run(world: &mut World, source: &mut impl InputSource, replay: &mut Replay) {
+    while world.time_alive < MAX_SECONDS {
+        let Some(input) = source.next_input(world) else {
+            break;
+        };
+        replay.inputs.push(input);
+        world.events.clear();
+        if step(world, input) {
+            break;
+        }
+    }
+}
+
+fn main() {
+    let runs: u64 = std::env::args()
+        .nth(1)
+        .and_then(|a| a.parse().ok())
+        .unwrap_or(100);
+    let hz: u32 = std::env::args()
+        .nth(2)
+        .and_then(|a| a.parse().ok())
+        .unwrap_or(TICK_RATE);
+    assert!(TICK_RATES.contains(&hz), "tick rate must be one of {:?}", TICK_RATES);
+
+    for mode in builtin_modes() {
+        let mut total_score = 0.0f64;
+        let mut total_time = 0.0f64;
+        for seed in 0..runs {
+            let mut replay = Replay::new(seed, mode.clone(), ARENA, hz);
+            let mut world = replay.world();
+            run(&mut world, &mut Bot, &mut replay);
+
+            let decoded = Replay::from_bytes(&replay.to_bytes()).expect("replay round-trip");
+            let mut check = decoded.world();
+            run(&mut check, &mut decoded.input(), &mut Replay::new(seed, mode.clone(), decoded.arena, hz));
+            assert_eq!((world.tick, world.score), (check.tick, check.score), "seed {} diverged", seed);
+
+            total_time += world.time_alive as f64;
+            total_score += world.score as f64;
+        }
+        println!(
+            "{:<12} runs: {:>5}  avg score: {:>8.1}  avg time: {:>6.1}s",
+            mode.name,
+            runs,
+            total_score / runs.max(1) as f64,
+            total_time / runs.max(1) as f64,
+        );
+    }
+}
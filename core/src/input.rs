@@ -0,0 +1,91 @@
+use glam::Vec2;
+
+use crate::world::World;
+
+// -------------------------------
+// Per-tick input
+// -------------------------------
+/// Everything `step()` needs to know about the player's intent for one tick.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TickInput {
+    pub dir: Vec2, // length <= 1.0
+    pub want_phase: bool,
+}
+
+/// Produces one `TickInput` per fixed step. Returning `None` means the source
+/// has run dry (end of a script or replay).
+pub trait InputSource {
+    fn next_input(&mut self, world: &World) -> Option<TickInput>;
+}
+
+// -------------------------------
+// Scripted: hold each input for a number of ticks
+// -------------------------------
+pub struct ScriptedInput {
+    steps: Vec<(u32, TickInput)>,
+    index: usize,
+    held: u32,
+}
+
+impl ScriptedInput {
+    pub fn new(steps: Vec<(u32, TickInput)>) -> Self {
+        Self { steps, index: 0, held: 0 }
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn next_input(&mut self, _world: &World) -> Option<TickInput> {
+        while let Some(&(ticks, input)) = self.steps.get(self.index) {
+            if self.held < ticks {
+                self.held += 1;
+                return Some(input);
+            }
+            self.index += 1;
+            self.held = 0;
+        }
+        None
+    }
+}
+
+// -------------------------------
+// Replay: exact per-tick stream
+// -------------------------------
+pub struct ReplayInput {
+    inputs: Vec<TickInput>,
+    cursor: usize,
+}
+
+impl ReplayInput {
+    pub fn new(inputs: Vec<TickInput>) -> Self {
+        Self { inputs, cursor: 0 }
+    }
+}
+
+impl InputSource for ReplayInput {
+    fn next_input(&mut self, _world: &World) -> Option<TickInput> {
+        let input = self.inputs.get(self.cursor).copied();
+        self.cursor += 1;
+        input
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mode::ModeConfig;
+    use crate::ARENA;
+
+    #[test]
+    fn scripted_input_holds_each_step_then_runs_dry() {
+        let w = World::new(ModeConfig::default(), 1, ARENA);
+        let phase = TickInput { dir: Vec2::ZERO, want_phase: true };
+        let right = TickInput { dir: Vec2::X, want_phase: false };
+        // Zero-tick steps are skipped, wherever they fall
+        let steps = vec![(0, right), (2, phase), (0, phase), (1, right), (0, phase)];
+        let mut s = ScriptedInput::new(steps);
+        let got: Vec<_> = std::iter::from_fn(|| s.next_input(&w)).collect();
+        assert_eq!(got, [phase, phase, right]);
+        assert_eq!(s.next_input(&w), None);
+        assert_eq!(ScriptedInput::new(Vec::new()).next_input(&w), None);
+    }
+}
@@ -0,0 +1,59 @@
+//! Feedback Rush simulation, free of any windowing, rendering or audio.
+//!
+//! The game binary and headless tools both drive the same [`World`] through
+//! [`step`], one [`TickInput`] per tick of [`World::dt`] seconds.
+
+pub mod input;
+pub mod mode;
+pub mod obstacle;
+pub mod replay;
+pub mod rng;
+pub mod util;
+pub mod world;
+
+pub use glam::{vec2, Vec2};
+pub use input::{InputSource, ReplayInput, ScriptedInput, TickInput};
+pub use mode::{builtin_modes, ChainRule, GhostRule, ModeConfig, ModeError, OrbWeights, Tuning};
+pub use obstacle::{Layout, Obstacle, Shape};
+pub use replay::{Replay, ReplayError};
+pub use rng::Rng;
+pub use world::{
+    step, Chain, Event, Ghost, GhostContact, GhostKind, InputFrame, Orb, OrbKind, Pass, Player,
+    RunStats, World,
+};
+
+// -------------------------------
+// Config
+// -------------------------------
+// Defaults for `Tuning`; mode files override them per mode.
+pub const PLAYER_RADIUS: f32 = 12.0;
+pub const GHOST_RADIUS: f32 = 10.0;
+pub const ORB_RADIUS: f32 = 8.0;
+
+pub const ACCEL: f32 = 1600.0;
+pub const FRICTION: f32 = 5.5;
+pub const MAX_SPEED: f32 = 300.0;
+
+// Logical playfield size. The front end scales it to fit the window, so
+// every run plays on the same field whatever the screen.
+pub const ARENA: Vec2 = Vec2::new(960.0, 540.0);
+
+// Simulation ticks per second: the default, and the rates players can pick
+pub const TICK_RATE: u32 = 60;
+pub const TICK_RATES: [u32; 3] = [60, 120, 240];
+pub const INPUT_HISTORY_SECONDS: f32 = 12.0;
+pub const MAX_HISTORY_SECONDS: f32 = 60.0; // keeps the history's memory bounded
+pub const HISTORY_MERGE: f32 = 0.5; // pixels; closer frames in a still stretch merge
+pub const GHOST_WINDOW: [f32; 2] = [2.6, 5.0]; // seconds replayed, calm to max threat
+
+pub const PHASE_MAX: f32 = 1.5;
+pub const PHASE_DRAIN: f32 = 1.6; // per second
+pub const PHASE_REGEN: f32 = 0.6; // per second
+
+pub const ORB_SPAWN_BASE: f32 = 1.5; // seconds between spawns at start
+pub const ORB_SPAWN_MIN: f32 = 0.35; // fastest spawn
+pub const ORB_SAFE_RADIUS: f32 = 80.0; // avoid spawning on top of the player
+
+pub const COMBO_DECAY_PER_SEC: f32 = 0.25;
+
+pub const NEAR_MISS_MARGIN: f32 = 14.0;
@@ -0,0 +1,416 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::*;
+
+// -------------------------------
+// Game modes, defined in TOML
+// -------------------------------
+// Shipped modes are compiled in so the game always has something to play;
+// files on disk with the same `id` replace them.
+const BUILTIN_MODES: &[(&str, &str)] = &[
+    ("classic", include_str!("../../modes/classic.toml")),
+    ("time_attack", include_str!("../../modes/time_attack.toml")),
+    ("nightmare", include_str!("../../modes/nightmare.toml")),
+];
+
+/// Physics, phase and spawn constants. Any field a mode file leaves out takes
+/// the crate-level default.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Tuning {
+    pub player_radius: f32,
+    pub ghost_radius: f32,
+    pub orb_radius: f32,
+
+    pub accel: f32,
+    pub friction: f32,
+    pub max_speed: f32,
+
+    pub input_history_seconds: f32,
+    pub history_merge: f32, // pixels of drift a still stretch may have; 0 keeps every tick
+
+    pub phase_max: f32,
+    pub phase_drain: f32, // per second
+    pub phase_regen: f32, // per second
+
+    pub orb_spawn_base: f32, // seconds between spawns at start
+    pub orb_spawn_min: f32,  // fastest spawn
+    pub orb_safe_radius: f32,
+
+    pub combo_decay_per_sec: f32,
+
+    pub near_miss_margin: f32, // gap around a ghost that counts as close
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            player_radius: PLAYER_RADIUS,
+            ghost_radius: GHOST_RADIUS,
+            orb_radius: ORB_RADIUS,
+            accel: ACCEL,
+            friction: FRICTION,
+            max_speed: MAX_SPEED,
+            input_history_seconds: INPUT_HISTORY_SECONDS,
+            history_merge: HISTORY_MERGE,
+            phase_max: PHASE_MAX,
+            phase_drain: PHASE_DRAIN,
+            phase_regen: PHASE_REGEN,
+            orb_spawn_base: ORB_SPAWN_BASE,
+            orb_spawn_min: ORB_SPAWN_MIN,
+            orb_safe_radius: ORB_SAFE_RADIUS,
+            combo_decay_per_sec: COMBO_DECAY_PER_SEC,
+            near_miss_margin: NEAR_MISS_MARGIN,
+        }
+    }
+}
+
+impl Tuning {
+    /// Overlays the keys in `text` (flat `name = value` pairs, as in a mode
+    /// file's `[tuning]` table) on top of `self`.
+    pub fn patched(&self, text: &str) -> Result<Tuning, ModeError> {
+        let patch: toml::Table = toml::from_str(text).map_err(ModeError::Parse)?;
+        let mut table = toml::Table::try_from(self).map_err(|e| ModeError::Invalid(e.to_string()))?;
+        table.extend(patch);
+        let t: Tuning = table.try_into().map_err(ModeError::Parse)?;
+        t.validate().map_err(ModeError::Invalid)?;
+        Ok(t)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let positive = [
+            ("player_radius", self.player_radius),
+            ("ghost_radius", self.ghost_radius),
+            ("orb_radius", self.orb_radius),
+            ("max_speed", self.max_speed),
+            ("input_history_seconds", self.input_history_seconds),
+            ("phase_max", self.phase_max),
+            ("orb_spawn_base", self.orb_spawn_base),
+            ("orb_spawn_min", self.orb_spawn_min),
+        ];
+        for (name, v) in positive {
+            if !(v.is_finite() && v > 0.0) {
+                return Err(format!("`{}` must be greater than zero", name));
+            }
+        }
+        let non_negative = [
+            ("accel", self.accel),
+            ("friction", self.friction),
+            ("phase_drain", self.phase_drain),
+            ("phase_regen", self.phase_regen),
+            ("history_merge", self.history_merge),
+            ("orb_safe_radius", self.orb_safe_radius),
+            ("combo_decay_per_sec", self.combo_decay_per_sec),
+            ("near_miss_margin", self.near_miss_margin),
+        ];
+        for (name, v) in non_negative {
+            if !(v.is_finite() && v >= 0.0) {
+                return Err(format!("`{}` must not be negative", name));
+            }
+        }
+        if self.input_history_seconds > MAX_HISTORY_SECONDS {
+            return Err(format!("`input_history_seconds` must be at most {}", MAX_HISTORY_SECONDS));
+        }
+        Ok(())
+    }
+}
+
+/// One archetype a mode can spawn, once the threat level reaches
+/// `min_difficulty`. Eligible rules are picked between by `weight`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GhostRule {
+    pub kind: GhostKind,
+    #[serde(default)]
+    pub min_difficulty: f32,
+    #[serde(default = "one")]
+    pub weight: f32,
+}
+
+fn one() -> f32 {
+    1.0
+}
+
+/// Relative odds of each orb kind. Only plain score orbs by default.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OrbWeights {
+    pub score: f32,
+    pub phase_refill: f32,
+    pub slow_time: f32,
+    pub purge: f32,
+    pub multiplier: f32,
+    pub decoy: f32,
+}
+
+impl Default for OrbWeights {
+    fn default() -> Self {
+        Self { score: 1.0, phase_refill: 0.0, slow_time: 0.0, purge: 0.0, multiplier: 0.0, decoy: 0.0 }
+    }
+}
+
+impl OrbWeights {
+    pub fn weight(&self, kind: OrbKind) -> f32 {
+        match kind {
+            OrbKind::Score => self.score,
+            OrbKind::PhaseRefill => self.phase_refill,
+            OrbKind::SlowTime => self.slow_time,
+            OrbKind::Purge => self.purge,
+            OrbKind::Multiplier => self.multiplier,
+            OrbKind::Decoy => self.decoy,
+        }
+    }
+}
+
+/// Numbered orbs that pay `bonus` times combo when all are collected in
+/// order before their shared timer runs out.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChainRule {
+    pub every: f32,  // seconds between chains, counted while none is up
+    pub length: u32, // orbs per chain
+    pub window: f32, // seconds to collect them all
+    pub bonus: f32,
+}
+
+impl Default for ChainRule {
+    fn default() -> Self {
+        Self { every: 20.0, length: 4, window: 8.0, bonus: 400.0 }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModeConfig {
+    pub id: String, // stable key for leaderboards; defaults to the file name
+    pub name: String,
+    pub time_limit: Option<f32>,
+    pub ghost_speed_mul: f32,
+    pub difficulty_rate: f32,
+    pub spawn_rate_mul: f32,
+    pub ghost_flicker: bool,
+    pub ghost_invisible_far: bool,
+    pub ghost_contact: GhostContact,
+    pub ghost_window: [f32; 2], // seconds of path replayed, at threat 0 and from 12 up
+    pub ghosts: Vec<GhostRule>,
+    pub orbs: OrbWeights,
+    pub orb_ttl: Option<f32>,   // seconds before an orb fades away; omit to keep them
+    pub orb_drift_chance: f32,  // share of orbs that drift
+    pub orb_drift_speed: f32,   // pixels per second
+    pub chain: Option<ChainRule>,
+    pub layout: Layout,
+    pub tuning: Tuning,
+}
+
+impl Default for ModeConfig {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            name: String::new(),
+            time_limit: None,
+            ghost_speed_mul: 1.0,
+            difficulty_rate: 0.2,
+            spawn_rate_mul: 1.0,
+            ghost_flicker: false,
+            ghost_invisible_far: false,
+            ghost_contact: GhostContact::Off,
+            ghost_window: GHOST_WINDOW,
+            ghosts: vec![GhostRule { kind: GhostKind::Echo, min_difficulty: 0.0, weight: 1.0 }],
+            orbs: OrbWeights::default(),
+            orb_ttl: None,
+            orb_drift_chance: 0.0,
+            orb_drift_speed: 0.0,
+            chain: None,
+            layout: Layout::Open,
+            tuning: Tuning::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ModeError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Invalid(String),
+}
+
+impl fmt::Display for ModeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModeError::Io(e) => write!(f, "{}", e),
+            ModeError::Parse(e) => write!(f, "{}", e),
+            ModeError::Invalid(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ModeError {}
+
+impl ModeConfig {
+    /// Parses a mode file. `fallback_id` (usually the file stem) is used when
+    /// the file doesn't set `id`, and doubles as the display name.
+    pub fn from_toml(text: &str, fallback_id: &str) -> Result<ModeConfig, ModeError> {
+        let mut m: ModeConfig = toml::from_str(text).map_err(ModeError::Parse)?;
+        if m.id.is_empty() {
+            m.id = fallback_id.to_string();
+        }
+        if m.name.is_empty() {
+            m.name = m.id.clone();
+        }
+        m.validate().map_err(ModeError::Invalid)?;
+        Ok(m)
+    }
+
+    /// This mode's tuning with `text` laid over it (see `Tuning::patched`),
+    /// checked against the rest of the mode too.
+    pub fn patched_tuning(&self, text: &str) -> Result<Tuning, ModeError> {
+        let t = self.tuning.patched(text)?;
+        self.check_history(&t).map_err(ModeError::Invalid)?;
+        Ok(t)
+    }
+
+    /// Ghosts can only replay what the history still holds.
+    fn check_history(&self, t: &Tuning) -> Result<(), String> {
+        if self.ghost_window[1] > t.input_history_seconds {
+            return Err("`ghost_window` can't be longer than `input_history_seconds`".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn to_toml(&self) -> String {
+        // Plain data with no maps keyed by non-strings; this can't fail
+        toml::to_string(self).expect("mode config serializes")
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(limit) = self.time_limit {
+            if !(limit.is_finite() && limit > 0.0) {
+                return Err("`time_limit` must be greater than zero".to_string());
+            }
+        }
+        let positive = [
+            ("ghost_speed_mul", self.ghost_speed_mul),
+            ("spawn_rate_mul", self.spawn_rate_mul),
+        ];
+        for (name, v) in positive {
+            if !(v.is_finite() && v > 0.0) {
+                return Err(format!("`{}` must be greater than zero", name));
+            }
+        }
+        if !(self.difficulty_rate.is_finite() && self.difficulty_rate >= 0.0) {
+            return Err("`difficulty_rate` must not be negative".to_string());
+        }
+        let [short, long] = self.ghost_window;
+        if !(short.is_finite() && short > 0.0 && short <= long) {
+            return Err("`ghost_window` must be two positive lengths, shortest first".to_string());
+        }
+        self.check_history(&self.tuning)?;
+        if let Some(ttl) = self.orb_ttl {
+            if !(ttl.is_finite() && ttl > 0.0) {
+                return Err("`orb_ttl` must be greater than zero".to_string());
+            }
+        }
+        if !(0.0..=1.0).contains(&self.orb_drift_chance) {
+            return Err("`orb_drift_chance` must be between 0 and 1".to_string());
+        }
+        if !(self.orb_drift_speed.is_finite() && self.orb_drift_speed >= 0.0) {
+            return Err("`orb_drift_speed` must not be negative".to_string());
+        }
+        if let Some(c) = self.chain {
+            let ok = |v: f32| v.is_finite() && v > 0.0;
+            if !ok(c.every) || !ok(c.window) {
+                return Err("chain `every` and `window` must be greater than zero".to_string());
+            }
+            if c.length < 2 {
+                return Err("chain `length` must be at least 2".to_string());
+            }
+            if !(c.bonus.is_finite() && c.bonus >= 0.0) {
+                return Err("chain `bonus` must not be negative".to_string());
+            }
+        }
+        if self.ghosts.is_empty() {
+            return Err("`ghosts` needs at least one entry".to_string());
+        }
+        for r in &self.ghosts {
+            let ok = |v: f32| v.is_finite() && v >= 0.0;
+            if !ok(r.min_difficulty) || !ok(r.weight) {
+                return Err(format!(
+                    "ghost `{}`: `min_difficulty` and `weight` must not be negative",
+                    r.kind.name()
+                ));
+            }
+        }
+        for k in OrbKind::ALL {
+            let v = self.orbs.weight(k);
+            if !(v.is_finite() && v >= 0.0) {
+                return Err(format!("orb weight `{}` must not be negative", k.name()));
+            }
+        }
+        if OrbKind::ALL.iter().all(|&k| self.orbs.weight(k) == 0.0) {
+            return Err("`orbs` needs at least one non-zero weight".to_string());
+        }
+        self.tuning.validate()
+    }
+}
+
+/// The modes compiled into the game, in menu order.
+pub fn builtin_modes() -> Vec<ModeConfig> {
+    BUILTIN_MODES
+        .iter()
+        .map(|(id, text)| ModeConfig::from_toml(text, id).expect("built-in mode parses"))
+        .collect()
+}
+
+/// Every `*.toml` in `dir`, sorted by file name. Unreadable files are
+/// reported alongside their path rather than aborting the whole load.
+pub fn load_dir(dir: &Path) -> Vec<Result<ModeConfig, (String, ModeError)>> {
+    let Ok(read) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<_> = read
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|x| x == "toml"))
+        .collect();
+    paths.sort();
+    paths
+        .into_iter()
+        .map(|p| {
+            let label = p.display().to_string();
+            let stem = p.file_stem().and_then(|s| s.to_str()).unwrap_or_default().to_string();
+            fs::read_to_string(&p)
+                .map_err(ModeError::Io)
+                .and_then(|text| ModeConfig::from_toml(&text, &stem))
+                .map_err(|e| (label, e))
+        })
+        .collect()
+}
+
+/// Adds `extra` to `modes`, replacing any mode with the same id in place.
+pub fn merge_modes(modes: &mut Vec<ModeConfig>, extra: impl IntoIterator<Item = ModeConfig>) {
+    for m in extra {
+        match modes.iter_mut().find(|x| x.id == m.id) {
+            Some(slot) => *slot = m,
+            None => modes.push(m),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patched_tuning_keeps_the_ghost_window_recordable() {
+        let mode = ModeConfig { ghost_window: [3.0, 10.0], ..ModeConfig::default() };
+        assert!(mode.patched_tuning("input_history_seconds = 10.0").is_ok());
+        assert!(matches!(
+            mode.patched_tuning("input_history_seconds = 8.0"),
+            Err(ModeError::Invalid(_))
+        ));
+        // The plain tuning check alone can't see the window
+        assert!(mode.tuning.patched("input_history_seconds = 8.0").is_ok());
+    }
+}
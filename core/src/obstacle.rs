@@ -0,0 +1,232 @@
+use glam::{vec2, Vec2};
+use serde::{Deserialize, Serialize};
+
+// -------------------------------
+// Obstacles
+// -------------------------------
+// Solid to the player and to drifting orbs. Ghosts replay positions and pass
+// straight through, so a ghost never gets stuck on a barrier that moved.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    Pillar { radius: f32 },
+    Block { half: Vec2 },
+    Wall { to: Vec2, thickness: f32 }, // segment from the centre to centre + `to`
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Obstacle {
+    pub shape: Shape,
+    pub pos: Vec2, // where it is this tick
+    pub prev_pos: Vec2,
+    home: Vec2,
+    travel: Vec2, // moving barriers sway between home and home + travel
+    period: f32,  // seconds per round trip; zero for static obstacles
+}
+
+impl Obstacle {
+    pub fn fixed(shape: Shape, pos: Vec2) -> Self {
+        Self { shape, pos, prev_pos: pos, home: pos, travel: Vec2::ZERO, period: 0.0 }
+    }
+
+    pub fn moving(shape: Shape, pos: Vec2, travel: Vec2, period: f32) -> Self {
+        Self { shape, pos, prev_pos: pos, home: pos, travel, period }
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.period > 0.0
+    }
+
+    /// Places a moving barrier for time `t`; eases in and out at each end.
+    /// Plain arithmetic rather than `cos`, whose last bits vary between
+    /// platforms' math libraries and would let shared replays drift.
+    pub fn update(&mut self, t: f32) {
+        if self.is_moving() {
+            let x = 1.0 - (2.0 * (t / self.period).rem_euclid(1.0) - 1.0).abs(); // 0, 1, 0
+            let k = x * x * (3.0 - 2.0 * x);
+            self.pos = self.home + self.travel * k;
+        }
+    }
+
+    /// Where to draw it, `alpha` of the way from the last tick to this one.
+    pub fn render_pos(&self, alpha: f32) -> Vec2 {
+        self.prev_pos.lerp(self.pos, alpha)
+    }
+
+    /// If a circle at `p` overlaps this obstacle, the nearest position that
+    /// clears it and the surface normal there.
+    pub fn push_out(&self, p: Vec2, r: f32) -> Option<(Vec2, Vec2)> {
+        self.push_out_at(self.pos, p, r)
+    }
+
+    /// Whether a circle overlaps anywhere along this obstacle's sweep.
+    pub fn blocks(&self, p: Vec2, r: f32) -> bool {
+        let steps = if self.is_moving() { 8 } else { 0 };
+        (0..=steps).any(|i| {
+            let at = self.home + self.travel * (i as f32 / steps.max(1) as f32);
+            self.push_out_at(at, p, r).is_some()
+        })
+    }
+
+    fn push_out_at(&self, at: Vec2, p: Vec2, r: f32) -> Option<(Vec2, Vec2)> {
+        match self.shape {
+            Shape::Pillar { radius } => push_from_point(at, p, radius + r),
+            Shape::Wall { to, thickness } => {
+                let len2 = to.length_squared();
+                let k = if len2 > 0.0 { ((p - at).dot(to) / len2).clamp(0.0, 1.0) } else { 0.0 };
+                push_from_point(at + to * k, p, thickness * 0.5 + r)
+            }
+            Shape::Block { half } => {
+                let local = p - at;
+                let q = local.clamp(-half, half);
+                let d = local - q;
+                if d != Vec2::ZERO {
+                    let dist = d.length();
+                    if dist >= r {
+                        return None;
+                    }
+                    let n = d / dist;
+                    return Some((at + q + n * r, n));
+                }
+                // Centre inside the box: leave by the shallowest side
+                let (px, py) = (half.x - local.x.abs(), half.y - local.y.abs());
+                if px < py {
+                    let s = if local.x < 0.0 { -1.0 } else { 1.0 };
+                    Some((vec2(at.x + s * (half.x + r), p.y), vec2(s, 0.0)))
+                } else {
+                    let s = if local.y < 0.0 { -1.0 } else { 1.0 };
+                    Some((vec2(p.x, at.y + s * (half.y + r)), vec2(0.0, s)))
+                }
+            }
+        }
+    }
+}
+
+fn push_from_point(c: Vec2, p: Vec2, reach: f32) -> Option<(Vec2, Vec2)> {
+    let d = p - c;
+    let dist = d.length();
+    if dist >= reach {
+        return None;
+    }
+    let n = if dist > 0.0 { d / dist } else { vec2(0.0, -1.0) };
+    Some((c + n * reach, n))
+}
+
+// -------------------------------
+// Layouts
+// -------------------------------
+/// Built-in obstacle arrangements, picked per mode. Positions scale with the
+/// arena and always leave the centre, where the player starts, clear.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Layout {
+    #[default]
+    Open, // no obstacles
+    Pillars, // four round pillars
+    Cross,   // a broken plus of walls around the centre
+    Rooms,   // corner blocks that funnel play through the middle
+    Sliders, // two barriers sliding up and down, and a sweeping pillar
+}
+
+impl Layout {
+    pub fn build(self, arena: Vec2) -> Vec<Obstacle> {
+        let at = |x: f32, y: f32| vec2(arena.x * x, arena.y * y);
+        match self {
+            Layout::Open => Vec::new(),
+            Layout::Pillars => [(0.25, 0.3), (0.75, 0.3), (0.25, 0.7), (0.75, 0.7)]
+                .into_iter()
+                .map(|(x, y)| Obstacle::fixed(Shape::Pillar { radius: 28.0 }, at(x, y)))
+                .collect(),
+            Layout::Cross => {
+                let wall = |from: Vec2, to: Vec2| {
+                    Obstacle::fixed(Shape::Wall { to: to - from, thickness: 14.0 }, from)
+                };
+                vec![
+                    wall(at(0.1, 0.5), at(0.35, 0.5)),
+                    wall(at(0.65, 0.5), at(0.9, 0.5)),
+                    wall(at(0.5, 0.1), at(0.5, 0.3)),
+                    wall(at(0.5, 0.7), at(0.5, 0.9)),
+                ]
+            }
+            Layout::Rooms => {
+                let half = vec2(arena.x * 0.08, arena.y * 0.1);
+                [(0.2, 0.25), (0.8, 0.25), (0.2, 0.75), (0.8, 0.75)]
+                    .into_iter()
+                    .map(|(x, y)| Obstacle::fixed(Shape::Block { half }, at(x, y)))
+                    .collect()
+            }
+            Layout::Sliders => {
+                let bar = Shape::Block { half: vec2(10.0, arena.y * 0.12) };
+                let swing = vec2(0.0, arena.y * 0.6);
+                vec![
+                    Obstacle::moving(bar, at(0.3, 0.2), swing, 4.0),
+                    Obstacle::moving(bar, at(0.7, 0.8), -swing, 5.0),
+                    Obstacle::moving(
+                        Shape::Pillar { radius: 22.0 },
+                        at(0.15, 0.12),
+                        vec2(arena.x * 0.7, 0.0),
+                        7.0,
+                    ),
+                ]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        a.distance(b) < 1e-4
+    }
+
+    #[test]
+    fn pillar_pushes_out_radially() {
+        let o = Obstacle::fixed(Shape::Pillar { radius: 20.0 }, vec2(100.0, 100.0));
+        assert_eq!(o.push_out(vec2(131.0, 100.0), 10.0), None);
+        let (pos, n) = o.push_out(vec2(100.0, 125.0), 10.0).unwrap();
+        assert!(close(pos, vec2(100.0, 130.0)) && close(n, vec2(0.0, 1.0)));
+        // Dead centre has no direction of its own: out through the top
+        let (pos, n) = o.push_out(vec2(100.0, 100.0), 10.0).unwrap();
+        assert!(close(pos, vec2(100.0, 70.0)) && close(n, vec2(0.0, -1.0)));
+    }
+
+    #[test]
+    fn block_pushes_out_by_the_nearest_side() {
+        let o = Obstacle::fixed(Shape::Block { half: vec2(40.0, 20.0) }, vec2(100.0, 100.0));
+        assert_eq!(o.push_out(vec2(151.0, 100.0), 10.0), None);
+        let (pos, n) = o.push_out(vec2(145.0, 110.0), 10.0).unwrap();
+        assert!(close(pos, vec2(150.0, 110.0)) && close(n, vec2(1.0, 0.0)));
+        // Centre inside: leave by the shallowest side, here the bottom
+        let (pos, n) = o.push_out(vec2(90.0, 115.0), 10.0).unwrap();
+        assert!(close(pos, vec2(90.0, 130.0)) && close(n, vec2(0.0, 1.0)));
+    }
+
+    #[test]
+    fn wall_pushes_out_from_the_segment() {
+        let wall = Shape::Wall { to: vec2(100.0, 0.0), thickness: 10.0 };
+        let o = Obstacle::fixed(wall, vec2(0.0, 50.0));
+        let (pos, n) = o.push_out(vec2(50.0, 44.0), 5.0).unwrap();
+        assert!(close(pos, vec2(50.0, 40.0)) && close(n, vec2(0.0, -1.0)));
+        // Beyond the end, the end cap is round
+        assert_eq!(o.push_out(vec2(108.0, 58.0), 5.0), None);
+        let (pos, _) = o.push_out(vec2(106.0, 50.0), 5.0).unwrap();
+        assert!(close(pos, vec2(110.0, 50.0)));
+    }
+
+    #[test]
+    fn moving_obstacles_block_their_whole_sweep() {
+        let pillar = Shape::Pillar { radius: 10.0 };
+        let mut o = Obstacle::moving(pillar, vec2(100.0, 100.0), vec2(0.0, 200.0), 4.0);
+        let far_end = vec2(100.0, 300.0);
+        assert!(o.push_out(far_end, 5.0).is_none());
+        assert!(o.blocks(far_end, 5.0));
+        o.update(1.0);
+        assert!(close(o.pos, vec2(100.0, 200.0)));
+        o.update(2.0); // half a round trip: at the far end
+        assert!(close(o.pos, far_end));
+        o.update(4.0);
+        assert!(close(o.pos, vec2(100.0, 100.0)));
+        assert!(!Obstacle::fixed(pillar, vec2(100.0, 100.0)).blocks(far_end, 5.0));
+    }
+}
@@ -0,0 +1,272 @@
+use glam::{vec2, Vec2};
+use std::fmt;
+
+use crate::input::{ReplayInput, TickInput};
+use crate::mode::ModeConfig;
+use crate::world::World;
+use crate::TICK_RATES;
+
+// -------------------------------
+// Replay: everything needed to re-run a game through `step()`
+// -------------------------------
+const MAGIC: &[u8; 4] = b"FRRP";
+// Bumped whenever the format or `step()` changes: an older file's inputs
+// would play out a different run, so only the current version is read.
+const VERSION: u16 = 6;
+
+// Longest run a file may claim: two hours at the highest tick rate. Anything
+// beyond that is corrupt, and decoding it would exhaust memory.
+const MAX_INPUTS: u64 = 240 * 60 * 60 * 2;
+
+const FLAG_PHASE: u8 = 1 << 0;
+const FLAG_DIR: u8 = 1 << 1;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub seed: u64,
+    pub mode: ModeConfig,
+    pub arena: Vec2,
+    pub tick_rate: u32,
+    pub inputs: Vec<TickInput>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ReplayError {
+    BadMagic,
+    UnsupportedVersion(u16),
+    BadMode(String),
+    BadTickRate(u64),
+    TooLong(u64),
+    Truncated,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::BadMagic => write!(f, "not a replay file"),
+            ReplayError::UnsupportedVersion(v) => write!(f, "unsupported replay version {}", v),
+            ReplayError::BadMode(e) => write!(f, "bad mode in replay: {}", e),
+            ReplayError::BadTickRate(hz) => write!(f, "unsupported tick rate {} Hz", hz),
+            ReplayError::TooLong(n) => write!(f, "replay claims {} ticks, more than any run", n),
+            ReplayError::Truncated => write!(f, "replay file is truncated"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Replay {
+    pub fn new(seed: u64, mode: ModeConfig, arena: Vec2, tick_rate: u32) -> Self {
+        Self { seed, mode, arena, tick_rate, inputs: Vec::new() }
+    }
+
+    /// A fresh world in the exact starting state of the recorded run.
+    pub fn world(&self) -> World {
+        World::with_tick_rate(self.mode.clone(), self.seed, self.arena, self.tick_rate)
+    }
+
+    pub fn input(&self) -> ReplayInput {
+        ReplayInput::new(self.inputs.clone())
+    }
+
+    /// Inputs are run-length encoded: held keys cost a few bytes per change
+    /// rather than per tick. Directions are stored as raw f32 so playback is
+    /// bit-exact.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&VERSION.to_le_bytes());
+        out.extend_from_slice(&self.seed.to_le_bytes());
+        let mode = self.mode.to_toml();
+        write_varint(&mut out, mode.len() as u64);
+        out.extend_from_slice(mode.as_bytes());
+        out.extend_from_slice(&self.arena.x.to_le_bytes());
+        out.extend_from_slice(&self.arena.y.to_le_bytes());
+        write_varint(&mut out, self.tick_rate as u64);
+        write_varint(&mut out, self.inputs.len() as u64);
+
+        let mut i = 0;
+        while i < self.inputs.len() {
+            let cur = self.inputs[i];
+            let run = self.inputs[i..].iter().take_while(|&&x| x == cur).count();
+            write_varint(&mut out, run as u64);
+            let mut flags = 0u8;
+            if cur.want_phase {
+                flags |= FLAG_PHASE;
+            }
+            if cur.dir != Vec2::ZERO {
+                flags |= FLAG_DIR;
+            }
+            out.push(flags);
+            if flags & FLAG_DIR != 0 {
+                out.extend_from_slice(&cur.dir.x.to_le_bytes());
+                out.extend_from_slice(&cur.dir.y.to_le_bytes());
+            }
+            i += run;
+        }
+        out
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Replay, ReplayError> {
+        let mut r = Reader { bytes, pos: 0 };
+        if r.take(4)? != MAGIC {
+            return Err(ReplayError::BadMagic);
+        }
+        let version = u16::from_le_bytes(r.array()?);
+        if version != VERSION {
+            return Err(ReplayError::UnsupportedVersion(version));
+        }
+        let seed = u64::from_le_bytes(r.array()?);
+        let len = r.varint()? as usize;
+        let text =
+            std::str::from_utf8(r.take(len)?).map_err(|e| ReplayError::BadMode(e.to_string()))?;
+        let mode =
+            ModeConfig::from_toml(text, "replay").map_err(|e| ReplayError::BadMode(e.to_string()))?;
+        let arena = vec2(f32::from_le_bytes(r.array()?), f32::from_le_bytes(r.array()?));
+        let hz = r.varint()?;
+        let tick_rate = TICK_RATES
+            .into_iter()
+            .find(|&r| r as u64 == hz)
+            .ok_or(ReplayError::BadTickRate(hz))?;
+
+        let total = r.varint()?;
+        if total > MAX_INPUTS {
+            return Err(ReplayError::TooLong(total));
+        }
+        let total = total as usize;
+        let mut inputs = Vec::with_capacity(total.min(1 << 20));
+        while inputs.len() < total {
+            let run = r.varint()?;
+            let flags = r.take(1)?[0];
+            let dir = if flags & FLAG_DIR != 0 {
+                vec2(f32::from_le_bytes(r.array()?), f32::from_le_bytes(r.array()?))
+            } else {
+                Vec2::ZERO
+            };
+            let input = TickInput { dir, want_phase: flags & FLAG_PHASE != 0 };
+            // A run can't be empty or reach past the count in the header
+            let end = usize::try_from(run)
+                .ok()
+                .and_then(|run| inputs.len().checked_add(run))
+                .filter(|&end| run > 0 && end <= total)
+                .ok_or(ReplayError::Truncated)?;
+            inputs.resize(end, input);
+        }
+
+        Ok(Replay { seed, mode, arena, tick_rate, inputs })
+    }
+}
+
+// -------------------------------
+// Byte helpers
+// -------------------------------
+fn write_varint(out: &mut Vec<u8>, mut v: u64) {
+    loop {
+        let byte = (v & 0x7f) as u8;
+        v >>= 7;
+        if v == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], ReplayError> {
+        let end = self.pos.checked_add(n).ok_or(ReplayError::Truncated)?;
+        let s = self.bytes.get(self.pos..end).ok_or(ReplayError::Truncated)?;
+        self.pos = end;
+        Ok(s)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+        let mut a = [0u8; N];
+        a.copy_from_slice(self.take(N)?);
+        Ok(a)
+    }
+
+    fn varint(&mut self) -> Result<u64, ReplayError> {
+        let mut v = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            v |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(v);
+            }
+        }
+        Err(ReplayError::Truncated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mode::builtin_modes;
+
+    fn sample() -> Replay {
+        let mode = builtin_modes().remove(0); // has an id, so it survives the trip as is
+        let mut r = Replay::new(42, mode, vec2(960.0, 540.0), 120);
+        let right = TickInput { dir: vec2(1.0, 0.0), want_phase: false };
+        let phase = TickInput { dir: vec2(0.6, -0.8), want_phase: true };
+        r.inputs = [vec![right; 30], vec![TickInput::default(); 5], vec![phase; 12]].concat();
+        r
+    }
+
+    /// A valid file up to the input count, for splicing hostile input runs on.
+    fn header() -> Vec<u8> {
+        let mut bytes = Replay::new(1, ModeConfig::default(), vec2(960.0, 540.0), 60).to_bytes();
+        assert_eq!(bytes.pop(), Some(0)); // the zero input count
+        bytes
+    }
+
+    #[test]
+    fn round_trip() {
+        let r = sample();
+        assert_eq!(Replay::from_bytes(&r.to_bytes()), Ok(r));
+    }
+
+    #[test]
+    fn every_truncation_is_an_error() {
+        let bytes = sample().to_bytes();
+        for len in 0..bytes.len() {
+            assert!(Replay::from_bytes(&bytes[..len]).is_err(), "decoded {} bytes", len);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_header() {
+        assert_eq!(Replay::from_bytes(b"NOPE\x03\x00"), Err(ReplayError::BadMagic));
+        for version in [0, VERSION - 1, VERSION + 1] {
+            let mut bytes = sample().to_bytes();
+            bytes[4..6].copy_from_slice(&version.to_le_bytes());
+            assert_eq!(Replay::from_bytes(&bytes), Err(ReplayError::UnsupportedVersion(version)));
+        }
+    }
+
+    #[test]
+    fn rejects_huge_input_count() {
+        let mut bytes = header();
+        write_varint(&mut bytes, 1 << 36);
+        bytes.extend_from_slice(&[1, 0]);
+        assert_eq!(Replay::from_bytes(&bytes), Err(ReplayError::TooLong(1 << 36)));
+    }
+
+    #[test]
+    fn rejects_runs_past_the_count() {
+        for run in [0, 11, u64::MAX, usize::MAX as u64 - 2] {
+            let mut bytes = header();
+            write_varint(&mut bytes, 10);
+            write_varint(&mut bytes, 5);
+            bytes.push(0);
+            write_varint(&mut bytes, run);
+            bytes.push(0);
+            assert_eq!(Replay::from_bytes(&bytes), Err(ReplayError::Truncated), "run {}", run);
+        }
+    }
+}
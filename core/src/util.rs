@@ -0,0 +1,109 @@
+use glam::{vec2, Vec2};
+
+use crate::rng::Rng;
+
+// -------------------------------
+// Utils
+// -------------------------------
+pub fn clamp_rect(p: Vec2, r: f32, w: f32, h: f32) -> (Vec2, Vec2) {
+    let mut pos = p;
+    let mut norm = vec2(0.0, 0.0);
+    if pos.x - r < 0.0 {
+        pos.x = r;
+        norm.x = 1.0;
+    }
+    if pos.x + r > w {
+        pos.x = w - r;
+        norm.x = -1.0;
+    }
+    if pos.y - r < 0.0 {
+        pos.y = r;
+        norm.y = 1.0;
+    }
+    if pos.y + r > h {
+        pos.y = h - r;
+        norm.y = -1.0;
+    }
+    (pos, norm)
+}
+
+pub fn circle_overlap(a: Vec2, ar: f32, b: Vec2, br: f32) -> bool {
+    a.distance_squared(b) <= (ar + br) * (ar + br)
+}
+
+pub fn rand_pos_away_from(rng: &mut Rng, p: Vec2, min_dist: f32, w: f32, h: f32) -> Vec2 {
+    for _ in 0..64 {
+        let w1 = (w - 40.0).max(41.0);
+        let h1 = (h - 40.0).max(41.0);
+        let rp = vec2(rng.range(40.0, w1), rng.range(40.0, h1));
+        if rp.distance(p) >= min_dist {
+            return rp;
+        }
+    }
+    vec2(
+        (p.x + 200.0).clamp(40.0, (w - 40.0).max(40.0)),
+        (p.y + 150.0).clamp(40.0, (h - 40.0).max(40.0)),
+    )
+}
+
+/// A uniformly random direction. Drawn from a disc and normalized, so only
+/// a square root is involved: unlike sin/cos it rounds the same everywhere.
+pub fn rand_unit(rng: &mut Rng) -> Vec2 {
+    loop {
+        let v = vec2(rng.range(-1.0, 1.0), rng.range(-1.0, 1.0));
+        let len2 = v.length_squared();
+        if len2 > 0.01 && len2 <= 1.0 {
+            return v / len2.sqrt();
+        }
+    }
+}
+
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+// Anything that moves further than this in one tick wrapped around or
+// teleported, and is drawn where it landed rather than streaked across
+const SNAP_DIST: f32 = 64.0;
+
+/// Render position `alpha` (0..1) of the way from last tick to this one.
+pub fn interpolate(prev: Vec2, cur: Vec2, alpha: f32) -> Vec2 {
+    if prev.distance_squared(cur) > SNAP_DIST * SNAP_DIST {
+        return cur;
+    }
+    prev.lerp(cur, alpha)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamp_rect_keeps_circles_inside() {
+        let inside = vec2(100.0, 50.0);
+        assert_eq!(clamp_rect(inside, 10.0, 200.0, 100.0), (inside, Vec2::ZERO));
+        // Past the left and bottom edges: back in, normals pointing inward
+        let (pos, n) = clamp_rect(vec2(-5.0, 120.0), 10.0, 200.0, 100.0);
+        assert_eq!(pos, vec2(10.0, 90.0));
+        assert_eq!(n, vec2(1.0, -1.0));
+        let (pos, n) = clamp_rect(vec2(195.0, 5.0), 10.0, 200.0, 100.0);
+        assert_eq!(pos, vec2(190.0, 10.0));
+        assert_eq!(n, vec2(-1.0, 1.0));
+    }
+
+    #[test]
+    fn rand_unit_is_unit_length() {
+        let mut rng = Rng::new(3);
+        for _ in 0..1000 {
+            assert!((rand_unit(&mut rng).length() - 1.0).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn interpolate_snaps_across_teleports() {
+        let a = vec2(10.0, 10.0);
+        assert_eq!(interpolate(a, vec2(30.0, 10.0), 0.25), vec2(15.0, 10.0));
+        let far = a + vec2(SNAP_DIST + 1.0, 0.0);
+        assert_eq!(interpolate(a, far, 0.25), far);
+    }
+}
@@ -0,0 +1,1293 @@
+use glam::{vec2, Vec2};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::input::TickInput;
+use crate::mode::{GhostRule, ModeConfig, Tuning};
+use crate::obstacle::Obstacle;
+use crate::rng::{self, Rng};
+use crate::util::{circle_overlap, clamp_rect, interpolate, lerp, rand_pos_away_from, rand_unit};
+use crate::TICK_RATE;
+
+// -------------------------------
+// Game Data
+// -------------------------------
+/// Where the player was at `t` seconds. In the history `t` is run time; in a
+/// ghost's recording it counts from the start of the window.
+#[derive(Clone, Copy, Debug)]
+pub struct InputFrame {
+    pub t: f32,
+    pub pos: Vec2,
+}
+
+/// How a ghost moves. Reverse and mirror are baked into `samples` at spawn;
+/// the rest change how `samples` are walked each step.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GhostKind {
+    Echo,     // loops the recorded path
+    Reverse,  // loops it backwards
+    Mirror,   // loops it flipped left-to-right across the arena
+    PingPong, // forwards, then backwards, then forwards...
+    Shadow,   // trails the live player by a fixed delay
+    Homing,   // loops the path while drifting toward the player
+}
+
+impl GhostKind {
+    pub const ALL: [GhostKind; 6] = [
+        GhostKind::Echo,
+        GhostKind::Reverse,
+        GhostKind::Mirror,
+        GhostKind::PingPong,
+        GhostKind::Shadow,
+        GhostKind::Homing,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            GhostKind::Echo => "echo",
+            GhostKind::Reverse => "reverse",
+            GhostKind::Mirror => "mirror",
+            GhostKind::PingPong => "ping_pong",
+            GhostKind::Shadow => "shadow",
+            GhostKind::Homing => "homing",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<GhostKind> {
+        GhostKind::ALL.into_iter().find(|k| k.name() == name)
+    }
+}
+
+// A ghost needs its whole window recorded, and at least this much path to
+// replay
+const GHOST_MIN_SECS: f32 = 0.2;
+// Ghosts spawned on the same tick (a decoy's pair, or several orbs at once)
+// would replay one path in lockstep; each further one starts this many
+// seconds further along it
+const SPAWN_STAGGER: f32 = 0.4;
+
+// Player physics steps at this rate whatever the tick rate, several times a
+// tick at the lower ones, so movement comes out the same at every rate
+const PHYSICS_RATE: u32 = 240;
+
+// Shadow delay in seconds, shrinking as difficulty rises
+const SHADOW_LAG: (f32, f32) = (1.6, 0.9);
+// Homing drift toward the player: pixels per second, and how far from its
+// recorded path a homing ghost may stray
+const HOMING_PULL: f32 = 35.0;
+const HOMING_REACH: f32 = 140.0;
+
+// Ghost-to-ghost contact (see `GhostContact`)
+const MERGE_SLOWDOWN: f32 = 0.8;
+const MERGE_MAX_RADIUS_MUL: f32 = 2.5; // of the mode's ghost_radius
+const MERGE_MAX_TTL: f32 = 30.0;
+const ANNIHILATE_SCORE: f32 = 25.0; // times combo, per pair
+
+// Close calls, paid out when the ghost leaves `near_miss_margin`
+const NEAR_MISS_SCORE: f32 = 35.0; // times combo
+const NEAR_MISS_COMBO: f32 = 0.1;
+const PHASE_THROUGH_SCORE: f32 = 15.0; // times combo
+const PHASE_THROUGH_COMBO: f32 = 0.05;
+
+// Orb effects
+const ORB_SCORE: f32 = 45.0; // times combo
+const SLOW_TIME_SECS: f32 = 4.0;
+const SLOW_TIME_RATE: f32 = 0.5; // ghost playback speed while slowed
+const MULTIPLIER_SECS: f32 = 8.0;
+const MULTIPLIER: f32 = 2.0;
+const DECOY_GHOSTS: u32 = 2;
+
+/// Where a ghost stands in a close pass by the player.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Pass {
+    #[default]
+    Clear, // not within the margin
+    Near,    // within the margin, no phasing so far
+    Hedged,  // phased while near but never overlapped: no reward
+    Through, // overlapped while phasing
+    Spoiled, // overlapped without phasing (only survivable in god mode)
+}
+
+/// What happens when two ghosts touch. Set per mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GhostContact {
+    #[default]
+    Off, // they pass through each other
+    Merge,      // one bigger, slower ghost with the lifetimes combined
+    Annihilate, // both vanish
+}
+
+#[derive(Clone)]
+pub struct Ghost {
+    pub id: u32, // spawn order, unique within a run
+    pub kind: GhostKind,
+    pub samples: Vec<InputFrame>, // for a shadow, a delay line ending at the player
+    pub progress: f32,            // seconds into the recording
+    pub speed: f32,         // playback rate, 1.0 == real time
+    pub radius: f32,
+    pub ttl: f32,     // seconds to live
+    pub age: f32,     // seconds since spawn
+    pub offset: Vec2, // homing drift, added to the recorded path
+    pub pass: Pass,
+    pub prev_pos: Vec2, // `current_pos()` as of the previous tick
+    pub armed: bool,    // false until it first clears the player after spawning
+}
+
+impl Ghost {
+    pub fn current_pos(&self) -> Vec2 {
+        let (Some(first), Some(last)) = (self.samples.first(), self.samples.last()) else {
+            return vec2(0.0, 0.0);
+        };
+        if self.kind == GhostKind::Shadow {
+            return self.sample_at(self.progress);
+        }
+        let span = last.t - first.t;
+        if span <= 0.0 {
+            return first.pos + self.offset;
+        }
+        let at = if self.kind == GhostKind::PingPong {
+            // Triangle wave over the recording
+            let q = self.progress.rem_euclid(2.0 * span);
+            if q > span { 2.0 * span - q } else { q }
+        } else {
+            self.progress.rem_euclid(span)
+        };
+        self.sample_at(first.t + at) + self.offset
+    }
+
+    /// Starts playback `lead` seconds further on. A shadow keeps at least
+    /// half its lag.
+    fn stagger(&mut self, lead: f32) {
+        let lead = if self.kind == GhostKind::Shadow {
+            let head = self.samples.last().map_or(0.0, |f| f.t);
+            lead.min(0.5 * (head - self.progress))
+        } else {
+            lead
+        };
+        self.progress += lead;
+        self.prev_pos = self.current_pos();
+    }
+
+    /// Seconds of path the recording covers.
+    pub fn duration(&self) -> f32 {
+        match (self.samples.first(), self.samples.last()) {
+            (Some(a), Some(b)) => b.t - a.t,
+            _ => 0.0,
+        }
+    }
+
+    /// The recorded position at `t`, between the samples either side.
+    fn sample_at(&self, t: f32) -> Vec2 {
+        let i = self.samples.partition_point(|f| f.t <= t);
+        match (i.checked_sub(1).map(|j| self.samples[j]), self.samples.get(i)) {
+            (Some(a), Some(b)) => a.pos.lerp(b.pos, (t - a.t) / (b.t - a.t)),
+            (Some(a), None) => a.pos,
+            (None, Some(b)) => b.pos,
+            (None, None) => Vec2::ZERO,
+        }
+    }
+
+    /// Where to draw it, `alpha` of the way from the last tick to this one.
+    pub fn render_pos(&self, alpha: f32) -> Vec2 {
+        interpolate(self.prev_pos, self.current_pos(), alpha)
+    }
+
+    /// Moves on by one `dt`-second tick. `rate` scales playback (slow time);
+    /// lifetimes and the shadow's delay still run in real time.
+    fn advance(&mut self, player: Vec2, rate: f32, dt: f32) {
+        self.ttl -= dt;
+        self.age += dt;
+        match self.kind {
+            GhostKind::Shadow => {
+                // Newest in, oldest out: the head is always `lag` behind
+                let t = self.samples.last().map_or(0.0, |f| f.t) + dt;
+                self.samples.push(InputFrame { t, pos: player });
+                self.progress += dt;
+                let behind = self.samples.partition_point(|f| f.t <= self.progress);
+                self.samples.drain(..behind.saturating_sub(1));
+            }
+            GhostKind::Homing => {
+                let to = player - self.current_pos();
+                self.offset += to.clamp_length_max(HOMING_PULL * rate * dt);
+                self.offset = self.offset.clamp_length_max(HOMING_REACH);
+                self.progress += self.speed * rate * dt;
+            }
+            _ => self.progress += self.speed * rate * dt,
+        }
+    }
+}
+
+/// What an orb does when collected. Every kind but the decoy also scores and
+/// spawns a ghost like a plain orb.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OrbKind {
+    Score,
+    PhaseRefill, // tops up phase energy
+    SlowTime,    // ghosts play back at half speed for a few seconds
+    Purge,       // removes the oldest ghost
+    Multiplier,  // doubles score for a few seconds
+    Decoy,       // no score, resets combo and spawns two ghosts
+}
+
+impl OrbKind {
+    pub const ALL: [OrbKind; 6] = [
+        OrbKind::Score,
+        OrbKind::PhaseRefill,
+        OrbKind::SlowTime,
+        OrbKind::Purge,
+        OrbKind::Multiplier,
+        OrbKind::Decoy,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            OrbKind::Score => "score",
+            OrbKind::PhaseRefill => "phase_refill",
+            OrbKind::SlowTime => "slow_time",
+            OrbKind::Purge => "purge",
+            OrbKind::Multiplier => "multiplier",
+            OrbKind::Decoy => "decoy",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Orb {
+    pub kind: OrbKind,
+    pub pos: Vec2,
+    pub prev_pos: Vec2,
+    pub vel: Vec2, // zero unless drifting
+    pub radius: f32,
+    pub ttl: Option<f32>, // seconds left, if it expires
+    pub chain: Option<u32>, // 1-based place in the current chain
+    pub alive: bool,
+}
+
+/// The chain currently in the arena.
+#[derive(Clone, Copy, Debug)]
+pub struct Chain {
+    pub next: u32, // number to collect next
+    pub len: u32,
+}
+
+// -------------------------------
+// Player
+// -------------------------------
+#[derive(Clone)]
+pub struct Player {
+    pub pos: Vec2,
+    pub prev_pos: Vec2,
+    pub vel: Vec2,
+    pub radius: f32,
+    pub phase_energy: f32,
+    pub phase_active: bool,
+}
+
+impl Player {
+    pub fn new(pos: Vec2, tuning: &Tuning) -> Self {
+        Self {
+            pos,
+            prev_pos: pos,
+            vel: vec2(0.0, 0.0),
+            radius: tuning.player_radius,
+            phase_energy: tuning.phase_max,
+            phase_active: false,
+        }
+    }
+}
+
+// -------------------------------
+// Events
+// -------------------------------
+/// Things that happened during a step, for the front end to react to with
+/// sound, shake and the like. Drained by whoever owns the world.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    OrbCollected { count: u32 },
+    PowerUp { kind: OrbKind, pos: Vec2 }, // any orb but a plain score orb
+    ChainCompleted { pos: Vec2, points: f32 },
+    ChainBroken { pos: Vec2 }, // out of order or out of time
+    GhostSpawned,
+    GhostsMerged { pos: Vec2 },
+    GhostsAnnihilated { pos: Vec2 },
+    NearMiss { pos: Vec2, points: f32 },
+    PhasedThrough { pos: Vec2, points: f32 },
+    Died { killer: u32 }, // the ghost's id
+}
+
+/// Tallies for the results screen and leaderboard.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RunStats {
+    pub peak_combo: f32,
+    pub orbs_collected: u32,
+    pub ghosts_spawned: u32,
+    pub near_misses: u32,
+    pub phase_throughs: u32,
+}
+
+// -------------------------------
+// Game State
+// -------------------------------
+#[derive(Clone)]
+pub struct World {
+    pub player: Player,
+    pub ghosts: Vec<Ghost>,
+    pub orbs: Vec<Orb>,
+
+    // Playfield bounds, fixed for the whole run, and what's in it
+    pub arena: Vec2,
+    pub obstacles: Vec<Obstacle>,
+
+    // History buffer for ghosts: a frame per tick, fewer while standing still
+    pub history: VecDeque<InputFrame>,
+    pub history_max: usize, // hard cap: a frame per tick, and one either side
+
+    // Simulation rate, fixed for the whole run: `dt` is 1 / `tick_rate`
+    pub tick_rate: u32,
+    pub dt: f32,
+
+    // Determinism: every gameplay roll comes from `rng`
+    pub seed: u64,
+    pub rng: Rng,
+
+    // Timers and progression
+    pub tick: u64,
+    pub time_alive: f32,
+    pub score: f32,
+    pub combo: f32,
+    pub last_collect_time: f32,
+    pub stats: RunStats,
+
+    pub orb_spawn_timer: f32,
+    pub chain_timer: f32,
+    pub chain: Option<Chain>,
+
+    // Power-up timers, in seconds left
+    pub slow_t: f32,
+    pub multiplier_t: f32,
+
+    // Difficulty dial
+    pub difficulty: f32,
+
+    // Camera shake
+    pub shake_t: f32,
+    pub shake_amt: f32,
+
+    // Output for the front end
+    pub events: Vec<Event>,
+
+    // Developer overrides
+    pub god: bool,
+    pub difficulty_override: Option<f32>,
+
+    // Rules for this run
+    pub config: ModeConfig,
+}
+
+impl World {
+    pub fn new(config: ModeConfig, seed: u64, arena: Vec2) -> World {
+        Self::with_tick_rate(config, seed, arena, TICK_RATE)
+    }
+
+    /// A world stepped `hz` times per second. Everything in the rules is in
+    /// seconds, so a run plays the same at any rate.
+    pub fn with_tick_rate(config: ModeConfig, seed: u64, arena: Vec2, hz: u32) -> World {
+        let hz = hz.max(1);
+        let dt = 1.0 / hz as f32;
+        let history_max = (config.tuning.input_history_seconds / dt).round() as usize + 2;
+
+        World {
+            player: Player::new(arena * 0.5, &config.tuning),
+            ghosts: Vec::new(),
+            orbs: Vec::new(),
+
+            arena,
+            obstacles: config.layout.build(arena),
+
+            history: VecDeque::with_capacity(history_max + 1),
+            history_max,
+
+            tick_rate: hz,
+            dt,
+
+            seed,
+            rng: Rng::new(seed),
+
+            tick: 0,
+            time_alive: 0.0,
+            score: 0.0,
+            combo: 1.0,
+            last_collect_time: -999.0,
+            stats: RunStats { peak_combo: 1.0, ..Default::default() },
+
+            orb_spawn_timer: 0.0,
+            chain_timer: config.chain.map_or(0.0, |c| c.every),
+            chain: None,
+
+            slow_t: 0.0,
+            multiplier_t: 0.0,
+
+            difficulty: 0.0,
+
+            shake_t: 0.0,
+            shake_amt: 0.0,
+
+            events: Vec::new(),
+
+            god: false,
+            difficulty_override: None,
+
+            config,
+        }
+    }
+
+    /// Swaps in new constants mid-run. Things already in the arena keep the
+    /// sizes they were spawned with; the player adopts the new ones.
+    pub fn apply_tuning(&mut self, t: Tuning) {
+        self.config.tuning = t;
+        self.player.radius = t.player_radius;
+        self.player.phase_energy = self.player.phase_energy.min(t.phase_max);
+        self.history_max = self.ticks(t.input_history_seconds) + 2;
+        while self.history.len() > self.history_max {
+            self.history.pop_front();
+        }
+    }
+
+    /// Adds the player's position to the history. While the player stays
+    /// within `history_merge` of where a still stretch began, the stretch's
+    /// end frame moves forward instead of a new frame being added; playback
+    /// between the two is then still too.
+    fn record_history(&mut self) {
+        let frame = InputFrame { t: self.time_alive, pos: self.player.pos };
+        let merge = self.config.tuning.history_merge;
+        let n = self.history.len();
+        let still = n >= 2
+            && merge > 0.0
+            && self.history[n - 2].pos.distance(frame.pos) <= merge
+            && self.history[n - 2].pos.distance(self.history[n - 1].pos) <= merge;
+        if still {
+            self.history[n - 1] = frame;
+        } else {
+            self.history.push_back(frame);
+        }
+        // Keep one frame from before the window so its start can be placed
+        let from = self.time_alive - self.config.tuning.input_history_seconds;
+        let stale = |h: &VecDeque<InputFrame>| h.get(1).is_some_and(|f| f.t <= from);
+        while self.history.len() > self.history_max || stale(&self.history) {
+            self.history.pop_front();
+        }
+    }
+
+    /// How many ticks `secs` seconds last at this world's rate.
+    pub fn ticks(&self, secs: f32) -> usize {
+        (secs / self.dt).round() as usize
+    }
+
+    pub fn difficulty_spawn_interval(&self) -> f32 {
+        let t = &self.config.tuning;
+        let s = t.orb_spawn_base * (1.0 / (1.0 + 0.25 * self.difficulty)) / self.config.spawn_rate_mul;
+        s.max(t.orb_spawn_min)
+    }
+
+    pub fn ghost_speed(&self) -> f32 {
+        // 1.0 plays the recording back in real time. Scale gently
+        (1.0 + 0.3 * self.difficulty) * self.config.ghost_speed_mul
+    }
+
+    pub fn ghost_ttl(&self) -> f32 {
+        // Longer lasting ghosts as difficulty increases, but cap it
+        (8.0 + self.difficulty * 2.0).min(18.0)
+    }
+
+    /// Picks an archetype from the mode's rules for the current difficulty.
+    /// Only rolls the RNG when there's an actual choice to make.
+    pub fn pick_ghost_kind(&mut self) -> GhostKind {
+        let d = self.difficulty;
+        let rules: Vec<GhostRule> = self
+            .config
+            .ghosts
+            .iter()
+            .filter(|r| d >= r.min_difficulty && r.weight > 0.0)
+            .copied()
+            .collect();
+        match rules.as_slice() {
+            [] => GhostKind::Echo,
+            [only] => only.kind,
+            _ => {
+                let total: f32 = rules.iter().map(|r| r.weight).sum();
+                let mut roll = self.rng.next_f32() * total;
+                for r in &rules {
+                    if roll < r.weight {
+                        return r.kind;
+                    }
+                    roll -= r.weight;
+                }
+                rules[rules.len() - 1].kind
+            }
+        }
+    }
+
+    pub fn spawn_ghost(&mut self, recent_secs: f32) {
+        let kind = self.pick_ghost_kind();
+        self.spawn_ghost_kind(kind, recent_secs);
+    }
+
+    pub fn spawn_ghost_kind(&mut self, kind: GhostKind, recent_secs: f32) {
+        let recent_secs = if kind == GhostKind::Shadow {
+            lerp(SHADOW_LAG.0, SHADOW_LAG.1, (self.difficulty / 12.0).min(1.0))
+        } else {
+            recent_secs
+        };
+        let from = self.time_alive - recent_secs;
+        if self.history.front().is_none_or(|f| f.t > from) {
+            return; // not enough data yet
+        }
+        // Start from the last frame at or before the window; if that was a
+        // still stretch, it's where the player stood when the window opened
+        let start = self.history.partition_point(|f| f.t <= from).saturating_sub(1);
+        let mut samples: Vec<InputFrame> = self
+            .history
+            .range(start..)
+            .map(|f| InputFrame { t: (f.t - from).max(0.0), pos: f.pos })
+            .collect();
+        if samples.last().is_none_or(|f| f.t < GHOST_MIN_SECS) {
+            return;
+        }
+        let mut progress = 0.0;
+        match kind {
+            GhostKind::Reverse => {
+                let end = samples.last().map_or(0.0, |f| f.t);
+                samples.reverse();
+                for f in &mut samples {
+                    f.t = end - f.t;
+                }
+                // The recording now opens where the player stands: start
+                // from the first point clear of them instead
+                let clear = self.player.radius
+                    + self.config.tuning.ghost_radius
+                    + self.config.tuning.near_miss_margin;
+                if let Some(f) = samples.iter().find(|f| f.pos.distance(self.player.pos) > clear) {
+                    progress = f.t;
+                }
+            }
+            GhostKind::Mirror => {
+                for f in &mut samples {
+                    f.pos.x = self.arena.x - f.pos.x;
+                }
+            }
+            _ => {}
+        }
+        let mut ghost = Ghost {
+            id: self.stats.ghosts_spawned,
+            kind,
+            samples,
+            progress,
+            speed: self.ghost_speed(),
+            radius: self.config.tuning.ghost_radius,
+            ttl: self.ghost_ttl(),
+            age: 0.0,
+            offset: Vec2::ZERO,
+            pass: Pass::Clear,
+            prev_pos: Vec2::ZERO, // set below, once it can be placed
+            armed: false,
+        };
+        ghost.prev_pos = ghost.current_pos();
+        self.ghosts.push(ghost);
+        self.stats.ghosts_spawned += 1;
+        self.events.push(Event::GhostSpawned);
+    }
+
+    /// Rolls an orb kind by the mode's weights. Modes with a single kind
+    /// never touch the RNG.
+    pub fn pick_orb_kind(&mut self) -> OrbKind {
+        let w = self.config.orbs;
+        let kinds: Vec<(OrbKind, f32)> = OrbKind::ALL
+            .into_iter()
+            .map(|k| (k, w.weight(k)))
+            .filter(|&(_, wt)| wt > 0.0)
+            .collect();
+        match kinds.as_slice() {
+            [] => OrbKind::Score,
+            [(only, _)] => *only,
+            _ => {
+                let total: f32 = kinds.iter().map(|&(_, wt)| wt).sum();
+                let mut roll = self.rng.next_f32() * total;
+                for &(k, wt) in &kinds {
+                    if roll < wt {
+                        return k;
+                    }
+                    roll -= wt;
+                }
+                kinds[kinds.len() - 1].0
+            }
+        }
+    }
+
+    pub fn spawn_orb(&mut self) {
+        let kind = self.pick_orb_kind();
+        let pos = self.orb_pos();
+        let (chance, speed) = (self.config.orb_drift_chance, self.config.orb_drift_speed);
+        let mut vel = Vec2::ZERO;
+        if chance > 0.0 && speed > 0.0 && (chance >= 1.0 || self.rng.next_f32() < chance) {
+            vel = rand_unit(&mut self.rng) * speed;
+        }
+        self.orbs.push(Orb {
+            kind,
+            pos,
+            prev_pos: pos,
+            vel,
+            radius: self.config.tuning.orb_radius,
+            ttl: self.config.orb_ttl,
+            chain: None,
+            alive: true,
+        });
+    }
+
+    /// Lays out a numbered chain of still score orbs sharing one timer.
+    pub fn spawn_chain(&mut self) {
+        let Some(rule) = self.config.chain else {
+            return;
+        };
+        for n in 1..=rule.length {
+            let pos = self.orb_pos();
+            self.orbs.push(Orb {
+                kind: OrbKind::Score,
+                pos,
+                prev_pos: pos,
+                vel: Vec2::ZERO,
+                radius: self.config.tuning.orb_radius,
+                ttl: Some(rule.window),
+                chain: Some(n),
+                alive: true,
+            });
+        }
+        self.chain = Some(Chain { next: 1, len: rule.length });
+    }
+
+    /// Somewhere clear of the player and of every obstacle's sweep. Gives
+    /// up after a few tries rather than loop on a crowded layout.
+    fn orb_pos(&mut self) -> Vec2 {
+        let r = self.config.tuning.orb_radius;
+        let mut pos = Vec2::ZERO;
+        for _ in 0..16 {
+            pos = rand_pos_away_from(
+                &mut self.rng,
+                self.player.pos,
+                self.config.tuning.orb_safe_radius,
+                self.arena.x,
+                self.arena.y,
+            );
+            if !self.obstacles.iter().any(|o| o.blocks(pos, r)) {
+                break;
+            }
+        }
+        pos
+    }
+
+    /// Adds to the score, doubled while a multiplier is running. Returns
+    /// what was actually added.
+    pub fn award(&mut self, points: f32) -> f32 {
+        let mul = if self.multiplier_t > 0.0 { MULTIPLIER } else { 1.0 };
+        self.score += points * mul;
+        points * mul
+    }
+
+    /// A copy for rendering later (the kill-cam), without the input history
+    /// or pending events.
+    pub fn snapshot(&self) -> World {
+        let mut w = self.clone();
+        w.history = VecDeque::new();
+        w.events.clear();
+        w
+    }
+
+    pub fn add_shake(&mut self, power: f32, time: f32) {
+        self.shake_amt = self.shake_amt.max(power);
+        self.shake_t = self.shake_t.max(time);
+    }
+
+    pub fn camera_offset(&self) -> Vec2 {
+        if self.shake_t <= 0.0 {
+            return vec2(0.0, 0.0);
+        }
+        // Hash the tick instead of drawing from `rng` so rendering never
+        // perturbs the simulation
+        let mut r = Rng::new(rng::mix(self.seed ^ self.tick));
+        vec2(
+            r.range(-1.0, 1.0) * self.shake_amt,
+            r.range(-1.0, 1.0) * self.shake_amt,
+        )
+    }
+}
+
+// -------------------------------
+// One fixed-timestep step
+// Returns true on game over
+// -------------------------------
+pub fn step(w: &mut World, input: TickInput) -> bool {
+    let (sw, sh) = (w.arena.x, w.arena.y);
+    let t = w.config.tuning;
+    let dt = w.dt;
+    w.tick += 1;
+    w.time_alive += dt;
+
+    // Remember where everything was, for render interpolation
+    w.player.prev_pos = w.player.pos;
+    for g in &mut w.ghosts {
+        g.prev_pos = g.current_pos();
+    }
+    for o in &mut w.orbs {
+        o.prev_pos = o.pos;
+    }
+    for o in &mut w.obstacles {
+        o.prev_pos = o.pos;
+    }
+    w.difficulty = w
+        .difficulty_override
+        .unwrap_or(w.config.difficulty_rate * w.time_alive + 0.002 * w.score); // mode ramp
+
+    if let Some(limit) = w.config.time_limit {
+        if w.time_alive >= limit {
+            return true;
+        }
+    }
+
+    // Spawn orbs over time
+    w.orb_spawn_timer -= dt;
+    if w.orb_spawn_timer <= 0.0 {
+        w.spawn_orb();
+        w.orb_spawn_timer = w.difficulty_spawn_interval();
+    }
+    if let Some(rule) = w.config.chain {
+        if w.chain.is_none() {
+            w.chain_timer -= dt;
+            if w.chain_timer <= 0.0 {
+                w.spawn_chain();
+                w.chain_timer = rule.every;
+            }
+        }
+    }
+    update_orbs(w);
+
+    // Phase ability
+    if input.want_phase && w.player.phase_energy > 0.0 {
+        w.player.phase_active = true;
+        w.player.phase_energy -= t.phase_drain * dt;
+        if w.player.phase_energy <= 0.0 {
+            w.player.phase_energy = 0.0;
+            w.player.phase_active = false;
+        }
+    } else {
+        w.player.phase_active = false;
+        w.player.phase_energy = (w.player.phase_energy + t.phase_regen * dt).min(t.phase_max);
+    }
+
+    for o in &mut w.obstacles {
+        o.update(w.time_alive);
+    }
+
+    // Physics, in fixed sub-steps (see `PHYSICS_RATE`)
+    let substeps = (PHYSICS_RATE / w.tick_rate).max(1);
+    let h = dt / substeps as f32;
+    for _ in 0..substeps {
+        w.player.vel += input.dir * t.accel * h;
+        if w.player.vel.length() > t.max_speed {
+            w.player.vel = w.player.vel.normalize() * t.max_speed;
+        }
+        w.player.vel *= (1.0 - t.friction * h).max(0.0);
+        w.player.pos += w.player.vel * h;
+
+        // Obstacles: push out and slide along the surface
+        for o in &w.obstacles {
+            if let Some((pos, n)) = o.push_out(w.player.pos, w.player.radius) {
+                w.player.pos = pos;
+                let into = w.player.vel.dot(n);
+                if into < 0.0 {
+                    w.player.vel -= n * into;
+                }
+            }
+        }
+
+        // Bounds clamp
+        let (clamped, _n) = clamp_rect(w.player.pos, w.player.radius, sw, sh);
+        w.player.pos = clamped;
+    }
+
+    w.record_history();
+
+    // Power-up timers
+    w.slow_t = (w.slow_t - dt).max(0.0);
+    w.multiplier_t = (w.multiplier_t - dt).max(0.0);
+
+    // Update ghosts
+    let rate = if w.slow_t > 0.0 { SLOW_TIME_RATE } else { 1.0 };
+    for g in &mut w.ghosts {
+        g.advance(w.player.pos, rate, dt);
+    }
+    w.ghosts.retain(|g| g.ttl > 0.0);
+    resolve_ghost_contacts(w);
+
+    // Collisions with ghosts
+    if !w.player.phase_active && !w.god {
+        let p = &w.player;
+        let hit = w
+            .ghosts
+            .iter()
+            .find(|g| g.armed && circle_overlap(p.pos, p.radius, g.current_pos(), g.radius));
+        if let Some(g) = hit {
+            // game over
+            w.events.push(Event::Died { killer: g.id });
+            w.add_shake(8.0, 0.25);
+            return true;
+        }
+    }
+
+    track_close_passes(w);
+
+    // Collide with orbs
+    let mut taken = Vec::new();
+    for o in &mut w.orbs {
+        if o.alive && circle_overlap(w.player.pos, w.player.radius, o.pos, o.radius) {
+            o.alive = false;
+            taken.push((o.kind, o.pos, o.chain));
+        }
+    }
+    let mut collected_count = 0u32;
+    let mut decoys = 0u32;
+    for (kind, pos, chain) in taken {
+        if let Some(n) = chain {
+            advance_chain(w, n, pos);
+        }
+        if kind == OrbKind::Decoy {
+            w.combo = 1.0;
+            decoys += 1;
+            w.events.push(Event::PowerUp { kind, pos });
+            continue;
+        }
+        collected_count += 1;
+
+        // Score and combo
+        let since = (w.time_alive - w.last_collect_time).max(0.0);
+        if since < 1.6 {
+            w.combo += 0.25;
+        } else {
+            w.combo = (w.combo - t.combo_decay_per_sec * since).max(1.0);
+            w.combo += 0.15;
+        }
+        w.last_collect_time = w.time_alive;
+        w.stats.orbs_collected += 1;
+        w.stats.peak_combo = w.stats.peak_combo.max(w.combo);
+
+        // A multiplier orb pays out doubled already
+        match kind {
+            OrbKind::Score | OrbKind::Decoy => {}
+            OrbKind::PhaseRefill => w.player.phase_energy = t.phase_max,
+            OrbKind::SlowTime => w.slow_t = SLOW_TIME_SECS,
+            OrbKind::Purge => purge_oldest_ghost(w),
+            OrbKind::Multiplier => w.multiplier_t = MULTIPLIER_SECS,
+        }
+        if kind != OrbKind::Score {
+            w.events.push(Event::PowerUp { kind, pos });
+        }
+        w.award(ORB_SCORE * w.combo);
+    }
+    // Spawn ghosts: replay the mode's window, longer as difficulty rises
+    let [short, long] = w.config.ghost_window;
+    let secs = lerp(short, long, (w.difficulty / 12.0).min(1.0));
+    for i in 0..collected_count + decoys * DECOY_GHOSTS {
+        let before = w.ghosts.len();
+        w.spawn_ghost(secs);
+        if let Some(g) = w.ghosts.get_mut(before) {
+            g.stagger(i as f32 * SPAWN_STAGGER);
+        }
+    }
+    if collected_count > 0 {
+        w.events.push(Event::OrbCollected { count: collected_count });
+        w.add_shake(3.0, 0.12);
+    } else if decoys > 0 {
+        w.add_shake(5.0, 0.18);
+    }
+    w.orbs.retain(|o| o.alive);
+
+    // Passive score over time with combo influence that decays slowly
+    let decay = t.combo_decay_per_sec * dt;
+    w.combo = (w.combo - decay).max(1.0);
+    let passive = (2.0 + w.difficulty * 0.4) * dt * w.combo;
+    w.award(passive);
+
+    // Camera shake timer
+    if w.shake_t > 0.0 {
+        w.shake_t -= dt;
+        if w.shake_t <= 0.0 {
+            w.shake_t = 0.0;
+            w.shake_amt = 0.0;
+        }
+    }
+
+    false
+}
+
+/// Moves drifting orbs, bouncing them off the arena edge and obstacles, and
+/// counts down lifetimes. A chain orb running out ends its chain.
+fn update_orbs(w: &mut World) {
+    let (sw, sh) = (w.arena.x, w.arena.y);
+    let dt = w.dt;
+    let mut expired_chain = None;
+    for o in &mut w.orbs {
+        if o.vel != Vec2::ZERO {
+            let (pos, n) = clamp_rect(o.pos + o.vel * dt, o.radius, sw, sh);
+            o.pos = pos;
+            // Point each component away from whichever wall we hit
+            if n.x != 0.0 {
+                o.vel.x = n.x * o.vel.x.abs();
+            }
+            if n.y != 0.0 {
+                o.vel.y = n.y * o.vel.y.abs();
+            }
+            for ob in &w.obstacles {
+                if let Some((pos, n)) = ob.push_out(o.pos, o.radius) {
+                    o.pos = pos;
+                    let into = o.vel.dot(n);
+                    if into < 0.0 {
+                        o.vel -= 2.0 * into * n;
+                    }
+                }
+            }
+        }
+        if let Some(ttl) = &mut o.ttl {
+            *ttl -= dt;
+            if *ttl <= 0.0 && o.alive {
+                o.alive = false;
+                if o.chain.is_some() {
+                    expired_chain = Some(o.pos);
+                }
+            }
+        }
+    }
+    if let (Some(pos), Some(_)) = (expired_chain, w.chain) {
+        w.chain = None;
+        w.events.push(Event::ChainBroken { pos });
+    }
+}
+
+/// Collecting chain orb `n`: the next one in line moves the chain along,
+/// the last pays out, and anything else breaks it.
+fn advance_chain(w: &mut World, n: u32, pos: Vec2) {
+    let Some(chain) = &mut w.chain else {
+        return;
+    };
+    if n != chain.next {
+        w.chain = None;
+        for o in &mut w.orbs {
+            if o.chain.is_some() {
+                o.alive = false;
+            }
+        }
+        w.events.push(Event::ChainBroken { pos });
+        return;
+    }
+    if n < chain.len {
+        chain.next += 1;
+        return;
+    }
+    w.chain = None;
+    let bonus = w.config.chain.map_or(0.0, |c| c.bonus);
+    let points = w.award(bonus * w.combo);
+    w.events.push(Event::ChainCompleted { pos, points });
+    w.add_shake(5.0, 0.2);
+}
+
+/// Removes the ghost that has been around longest.
+fn purge_oldest_ghost(w: &mut World) {
+    let oldest = w
+        .ghosts
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.age.total_cmp(&b.age))
+        .map(|(i, _)| i);
+    if let Some(i) = oldest {
+        w.ghosts.remove(i);
+    }
+}
+
+/// Merges or annihilates pairs that have just come together, per the mode's
+/// `ghost_contact`. Ghosts that were already overlapping a tick ago (spawned
+/// together, or from a player standing still) are left alone until they
+/// part and meet again.
+fn resolve_ghost_contacts(w: &mut World) {
+    let rule = w.config.ghost_contact;
+    if rule == GhostContact::Off {
+        return;
+    }
+    let max_radius = w.config.tuning.ghost_radius * MERGE_MAX_RADIUS_MUL;
+    let mut i = 0;
+    'outer: while i < w.ghosts.len() {
+        let mut j = i + 1;
+        while j < w.ghosts.len() {
+            let (a, b) = (&w.ghosts[i], &w.ghosts[j]);
+            let (pa, pb) = (a.current_pos(), b.current_pos());
+            let touching = circle_overlap(pa, a.radius, pb, b.radius)
+                && !circle_overlap(a.prev_pos, a.radius, b.prev_pos, b.radius);
+            if !touching {
+                j += 1;
+                continue;
+            }
+            let pos = pa.lerp(pb, 0.5);
+            if rule == GhostContact::Annihilate {
+                w.ghosts.remove(j);
+                w.ghosts.remove(i);
+                w.award(ANNIHILATE_SCORE * w.combo);
+                w.events.push(Event::GhostsAnnihilated { pos });
+                w.add_shake(4.0, 0.15);
+                continue 'outer; // `i` now holds the next ghost
+            }
+            // Merge into whichever is bigger, then re-check it against the rest
+            let mut other = w.ghosts.remove(j);
+            let g = &mut w.ghosts[i];
+            if other.radius > g.radius {
+                std::mem::swap(g, &mut other);
+            }
+            g.radius = (g.radius * g.radius + other.radius * other.radius).sqrt().min(max_radius);
+            g.speed = g.speed.min(other.speed) * MERGE_SLOWDOWN;
+            g.ttl = (g.ttl + other.ttl).min(MERGE_MAX_TTL);
+            w.events.push(Event::GhostsMerged { pos });
+            j = i + 1;
+        }
+        i += 1;
+    }
+}
+
+/// Rewards ghosts that came within `near_miss_margin` of the player and
+/// left again: untouched without phasing is a near miss, overlapped while
+/// phasing is a phase-through.
+fn track_close_passes(w: &mut World) {
+    let p = &w.player;
+    let margin = w.config.tuning.near_miss_margin;
+    let mut payouts = Vec::new();
+    for g in &mut w.ghosts {
+        let pos = g.current_pos();
+        let dist = pos.distance(p.pos);
+        let touch = p.radius + g.radius;
+        if !g.armed {
+            // Spawned on or near the player (a shadow, or a path that ends
+            // where they stand): harmless, and worth nothing, until it's clear
+            g.armed = dist >= touch + margin;
+            continue;
+        }
+        g.pass = if dist >= touch + margin {
+            match g.pass {
+                Pass::Near => payouts.push((pos, false)),
+                Pass::Through => payouts.push((pos, true)),
+                _ => {}
+            }
+            Pass::Clear
+        } else if dist < touch {
+            match (g.pass, p.phase_active) {
+                (Pass::Spoiled, _) | (_, false) => Pass::Spoiled,
+                (_, true) => Pass::Through,
+            }
+        } else {
+            match (g.pass, p.phase_active) {
+                (Pass::Clear | Pass::Near, false) => Pass::Near,
+                (Pass::Clear | Pass::Near, true) => Pass::Hedged,
+                (other, _) => other,
+            }
+        };
+    }
+    for (pos, through) in payouts {
+        let (score, combo) = if through {
+            w.stats.phase_throughs += 1;
+            (PHASE_THROUGH_SCORE, PHASE_THROUGH_COMBO)
+        } else {
+            w.stats.near_misses += 1;
+            (NEAR_MISS_SCORE, NEAR_MISS_COMBO)
+        };
+        let points = w.award(score * w.combo);
+        w.combo += combo;
+        w.stats.peak_combo = w.stats.peak_combo.max(w.combo);
+        w.events.push(if through {
+            Event::PhasedThrough { pos, points }
+        } else {
+            Event::NearMiss { pos, points }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{InputSource, ScriptedInput};
+    use crate::mode::builtin_modes;
+    use crate::replay::Replay;
+    use crate::{ARENA, TICK_RATES};
+
+    fn held(dir: Vec2) -> TickInput {
+        TickInput { dir, want_phase: false }
+    }
+
+    /// Wanders about and phases now and then; the same stream every time.
+    fn wander(tick: u64) -> TickInput {
+        TickInput { dir: Vec2::from_angle(tick as f32 * 0.02), want_phase: tick % 180 < 20 }
+    }
+
+    /// Holds each direction for `secs` seconds in god mode.
+    fn walk(w: &mut World, legs: &[(Vec2, f32)]) {
+        let steps = legs.iter().map(|&(dir, secs)| (w.ticks(secs) as u32, held(dir))).collect();
+        let mut script = ScriptedInput::new(steps);
+        w.god = true;
+        while let Some(input) = script.next_input(w) {
+            step(w, input);
+        }
+        w.god = false;
+    }
+
+    /// A world whose player has walked a hook through the window, with
+    /// nothing else in the arena.
+    fn walked() -> World {
+        let mut w = World::new(ModeConfig::default(), 7, ARENA);
+        walk(&mut w, &[(vec2(1.0, 0.0), 1.0), (vec2(0.0, 1.0), 0.8), (vec2(-1.0, 0.0), 1.0)]);
+        w.ghosts.clear();
+        w.orbs.clear();
+        w
+    }
+
+    fn orb_at(kind: OrbKind, pos: Vec2) -> Orb {
+        let (vel, ttl, chain) = (Vec2::ZERO, None, None);
+        Orb { kind, pos, prev_pos: pos, vel, radius: 8.0, ttl, chain, alive: true }
+    }
+
+    /// Everything a diverging run would show up in, compared bit for bit.
+    fn fingerprint(w: &World) -> Vec<u32> {
+        let ghosts = w.ghosts.iter().map(|g| g.current_pos());
+        let orbs = w.orbs.iter().map(|o| o.pos);
+        let points = std::iter::once(w.player.pos).chain(ghosts).chain(orbs);
+        [w.tick as u32, w.score.to_bits(), w.ghosts.len() as u32, w.orbs.len() as u32]
+            .into_iter()
+            .chain(points.flat_map(|p| [p.x.to_bits(), p.y.to_bits()]))
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_and_input_same_run() {
+        for mode in builtin_modes() {
+            let run = || {
+                let mut w = World::new(mode.clone(), 99, ARENA);
+                w.god = true;
+                while w.tick < 1800 {
+                    let input = wander(w.tick);
+                    if step(&mut w, input) {
+                        break;
+                    }
+                }
+                assert!(w.stats.ghosts_spawned > 0, "{}: no ghosts to compare", mode.id);
+                fingerprint(&w)
+            };
+            let first = run();
+            assert_eq!(first, run(), "{}", mode.id);
+        }
+        let orbs = |seed| {
+            let mut w = World::new(ModeConfig::default(), seed, ARENA);
+            step(&mut w, TickInput::default());
+            w.orbs.iter().map(|o| o.pos).collect::<Vec<_>>()
+        };
+        assert_ne!(orbs(1), orbs(2));
+    }
+
+    #[test]
+    fn replay_reproduces_the_run() {
+        let mut w = World::with_tick_rate(builtin_modes().remove(0), 5, ARENA, 120);
+        let mut replay = Replay::new(w.seed, w.config.clone(), w.arena, w.tick_rate);
+        while w.tick < 2400 {
+            let input = wander(w.tick);
+            replay.inputs.push(input);
+            if step(&mut w, input) {
+                break;
+            }
+        }
+        let loaded = Replay::from_bytes(&replay.to_bytes()).unwrap();
+        let mut again = loaded.world();
+        for &input in &loaded.inputs {
+            step(&mut again, input);
+        }
+        assert_eq!(fingerprint(&again), fingerprint(&w));
+    }
+
+    #[test]
+    fn movement_is_the_same_at_every_tick_rate() {
+        let players: Vec<Player> = TICK_RATES
+            .into_iter()
+            .map(|hz| {
+                let mut w = World::with_tick_rate(ModeConfig::default(), 7, ARENA, hz);
+                walk(&mut w, &[(vec2(1.0, 0.0), 0.8), (vec2(0.6, -0.8), 0.4), (Vec2::ZERO, 0.2)]);
+                w.player
+            })
+            .collect();
+        let first = &players[0];
+        assert!(first.vel.length() > 30.0, "still coasting at the end");
+        for (p, hz) in players.iter().zip(TICK_RATES) {
+            assert!(p.pos.distance(first.pos) < 0.01, "{} Hz at {}", hz, p.pos);
+            assert!(p.vel.distance(first.vel) < 0.01, "{} Hz moving {}", hz, p.vel);
+        }
+    }
+
+    #[test]
+    fn new_ghosts_never_start_on_the_player() {
+        for kind in GhostKind::ALL {
+            let mut w = walked();
+            w.spawn_ghost_kind(kind, 2.6);
+            let g = &w.ghosts[0];
+            let dist = g.current_pos().distance(w.player.pos);
+            let touch = w.player.radius + g.radius;
+            // A reversed path opens where the player stands
+            if kind == GhostKind::Reverse {
+                assert!(dist > touch + w.config.tuning.near_miss_margin, "reverse at {}", dist);
+            }
+            assert!(dist > touch || !g.armed, "{:?} spawned armed on the player", kind);
+            assert!(!step(&mut w, TickInput::default()), "{:?} killed on spawn", kind);
+        }
+    }
+
+    #[test]
+    fn ghosts_on_a_still_player_are_harmless_until_clear() {
+        let mut w = World::new(ModeConfig::default(), 7, ARENA);
+        walk(&mut w, &[(Vec2::ZERO, 3.0)]);
+        w.orbs.clear();
+        w.spawn_ghost_kind(GhostKind::Echo, 2.6);
+        for _ in 0..w.ticks(1.0) {
+            assert!(!step(&mut w, TickInput::default()));
+        }
+        assert!(!w.ghosts[0].armed);
+        // Walking away arms it; walking back into it is then fatal
+        walk(&mut w, &[(vec2(1.0, 0.0), 0.5)]);
+        assert!(w.ghosts[0].armed);
+        let died = (0..w.ticks(2.0)).any(|_| step(&mut w, held(vec2(-1.0, 0.0))));
+        assert!(died);
+    }
+
+    #[test]
+    fn a_decoys_ghosts_are_apart() {
+        let mut w = walked();
+        w.orbs.push(orb_at(OrbKind::Decoy, w.player.pos));
+        step(&mut w, TickInput::default());
+        assert_eq!(w.ghosts.len(), DECOY_GHOSTS as usize);
+        let (a, b) = (w.ghosts[0].current_pos(), w.ghosts[1].current_pos());
+        assert!(a.distance(b) > 2.0 * w.config.tuning.ghost_radius, "{} and {}", a, b);
+    }
+
+    #[test]
+    fn ghosts_spawned_together_do_not_annihilate() {
+        let mut w = World::new(ModeConfig::default(), 7, ARENA);
+        w.config.ghost_contact = GhostContact::Annihilate;
+        walk(&mut w, &[(Vec2::ZERO, 3.0)]);
+        w.orbs.clear();
+        // A still path: staggering can't separate these
+        w.orbs.push(orb_at(OrbKind::Decoy, w.player.pos));
+        for _ in 0..w.ticks(1.0) {
+            step(&mut w, TickInput::default());
+        }
+        assert_eq!(w.ghosts.len(), DECOY_GHOSTS as usize);
+        assert!(!w.events.iter().any(|e| matches!(e, Event::GhostsAnnihilated { .. })));
+    }
+}
@@ -0,0 +1,225 @@
+use feedback_rush_core::{GhostKind, World};
+use macroquad::prelude::*;
+
+use crate::pacing::Pacing;
+use crate::view::arena_camera;
+use crate::{camera_offset, draw_obstacle, Settings};
+
+// -------------------------------
+// Developer console / debug overlay
+// -------------------------------
+// F3 toggles the overlay, ` opens the command line. Anything typed into the
+// console that changes the world marks the run as tampered.
+const LOG_LINES: usize = 6;
+const HELP: &str = "spawn_ghost [secs] [kind] | set difficulty <n|auto> | god | timescale <x>";
+const GHOST_ROWS: usize = 12;
+
+pub struct DevConsole {
+    pub overlay: bool,
+    pub open: bool,
+    pub timescale: f32,
+    line: String,
+    log: Vec<String>,
+}
+
+impl DevConsole {
+    pub fn new() -> Self {
+        Self {
+            overlay: false,
+            open: false,
+            timescale: 1.0,
+            line: String::new(),
+            log: vec!["type `help` for commands".to_string()],
+        }
+    }
+
+    /// Handles toggles and typing. Returns true if a command altered the
+    /// world or the flow of time.
+    pub fn update(&mut self, w: &mut World) -> bool {
+        if is_key_pressed(KeyCode::F3) {
+            self.overlay = !self.overlay;
+        }
+        if is_key_pressed(KeyCode::GraveAccent) {
+            self.open = !self.open;
+            self.line.clear();
+            clear_input_queue();
+            return false;
+        }
+        if !self.open {
+            return false;
+        }
+
+        while let Some(c) = get_char_pressed() {
+            if !c.is_control() && c != '`' {
+                self.line.push(c);
+            }
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.line.pop();
+        }
+        if is_key_pressed(KeyCode::Escape) {
+            self.open = false;
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            let line = std::mem::take(&mut self.line);
+            return self.run(line.trim(), w);
+        }
+        false
+    }
+
+    fn run(&mut self, line: &str, w: &mut World) -> bool {
+        if line.is_empty() {
+            return false;
+        }
+        self.print(format!("> {}", line));
+        let args: Vec<&str> = line.split_whitespace().collect();
+        // (reply, whether the command changed anything)
+        let (reply, changed) = match args.as_slice() {
+            ["help"] => (HELP.to_string(), false),
+            ["spawn_ghost"] => self.spawn_ghost(w, 3.0, None),
+            ["spawn_ghost", secs, rest @ ..] if rest.len() <= 1 => {
+                let kind = match rest.first() {
+                    None => Ok(None),
+                    Some(k) => GhostKind::from_name(k).map(Some).ok_or(k),
+                };
+                match (secs.parse::<f32>(), kind) {
+                    (_, Err(k)) => (format!("unknown ghost kind `{}`", k), false),
+                    (Ok(s), Ok(kind)) if s > 0.0 => self.spawn_ghost(w, s, kind),
+                    _ => (format!("bad duration `{}`", secs), false),
+                }
+            }
+            ["set", "difficulty", "auto"] => {
+                w.difficulty_override = None;
+                ("difficulty follows the mode ramp".to_string(), true)
+            }
+            ["set", "difficulty", v] => match v.parse::<f32>() {
+                Ok(d) if d.is_finite() && d >= 0.0 => {
+                    w.difficulty_override = Some(d);
+                    (format!("difficulty pinned at {}", d), true)
+                }
+                _ => (format!("bad difficulty `{}`", v), false),
+            },
+            ["god"] => {
+                w.god = !w.god;
+                (format!("god mode {}", if w.god { "on" } else { "off" }), true)
+            }
+            ["timescale", v] => match v.parse::<f32>() {
+                Ok(t) if (0.05..=4.0).contains(&t) => {
+                    self.timescale = t;
+                    (format!("timescale {}", t), true)
+                }
+                _ => ("timescale must be between 0.05 and 4".to_string(), false),
+            },
+            _ => (format!("unknown command `{}`", line), false),
+        };
+        self.print(reply);
+        changed
+    }
+
+    fn spawn_ghost(&mut self, w: &mut World, secs: f32, kind: Option<GhostKind>) -> (String, bool) {
+        let before = w.ghosts.len();
+        match kind {
+            Some(k) => w.spawn_ghost_kind(k, secs),
+            None => w.spawn_ghost(secs),
+        }
+        if let Some(g) = w.ghosts.get(before) {
+            (format!("spawned a {:.1}s {} ghost", secs, g.kind.name()), true)
+        } else {
+            ("not enough history yet".to_string(), false)
+        }
+    }
+
+    fn print(&mut self, msg: String) {
+        self.log.push(msg);
+        let extra = self.log.len().saturating_sub(LOG_LINES);
+        self.log.drain(..extra);
+    }
+
+    pub fn draw(&self, w: &World, settings: &Settings, pacing: &Pacing) {
+        if self.overlay {
+            draw_overlay(w, settings, pacing, self.timescale);
+        }
+        if self.open {
+            draw_prompt(&self.log, &self.line);
+        }
+    }
+}
+
+fn draw_overlay(w: &World, settings: &Settings, pacing: &Pacing, timescale: f32) {
+    let off = camera_offset(w, settings);
+    let outline = Color::new(0.3, 1.0, 0.4, 0.9);
+
+    // Collision radii, in arena space
+    set_camera(&arena_camera(w.arena));
+    draw_circle_lines(w.player.pos.x + off.x, w.player.pos.y + off.y, w.player.radius, 1.0, outline);
+    for g in &w.ghosts {
+        let p = g.current_pos();
+        draw_circle_lines(p.x + off.x, p.y + off.y, g.radius, 1.0, outline);
+    }
+    for o in &w.orbs {
+        draw_circle_lines(o.pos.x + off.x, o.pos.y + off.y, o.radius, 1.0, outline);
+    }
+    for o in &w.obstacles {
+        draw_obstacle(o, o.pos + off, outline, false);
+    }
+    set_default_camera();
+
+    // Stats panel, top right under the threat readout
+    let mut lines = vec![
+        format!("tick {} @ {} Hz", w.tick, w.tick_rate),
+        format!("lag {:.1}ms  timescale {:.2}", pacing.lag() * 1000.0, timescale),
+        format!(
+            "ticks/frame {}  merged {}  dropped {}",
+            pacing.frame_ticks(),
+            pacing.merged,
+            pacing.dropped
+        ),
+        format!(
+            "history {}/{} frames, {:.1}s",
+            w.history.len(),
+            w.history_max,
+            w.history.front().map_or(0.0, |f| w.time_alive - f.t)
+        ),
+        format!("difficulty {:.2}{}", w.difficulty, if w.difficulty_override.is_some() { " (pinned)" } else { "" }),
+        format!("ghosts {}{}", w.ghosts.len(), if w.god { "  GOD" } else { "" }),
+    ];
+    for (i, g) in w.ghosts.iter().take(GHOST_ROWS).enumerate() {
+        lines.push(format!(
+            " #{:<2} {:<9} at {:>5.2}s/{:<5.2} spd {:.2} ttl {:.1}",
+            i,
+            g.kind.name(),
+            g.progress,
+            g.duration(),
+            g.speed,
+            g.ttl
+        ));
+    }
+    if w.ghosts.len() > GHOST_ROWS {
+        lines.push(format!(" ... {} more", w.ghosts.len() - GHOST_ROWS));
+    }
+
+    let sw = screen_width();
+    let x = sw - 400.0;
+    let h = lines.len() as f32 * 18.0 + 10.0;
+    draw_rectangle(x - 8.0, 44.0, 400.0, h, Color::new(0.0, 0.0, 0.0, 0.55));
+    let mut y = 60.0;
+    for l in &lines {
+        draw_text(l, x, y, 18.0, outline);
+        y += 18.0;
+    }
+}
+
+fn draw_prompt(log: &[String], line: &str) {
+    let sw = screen_width();
+    let sh = screen_height();
+    let h = (log.len() + 1) as f32 * 20.0 + 12.0;
+    let y0 = sh - h;
+    draw_rectangle(0.0, y0, sw, h, Color::new(0.0, 0.0, 0.0, 0.75));
+    let mut y = y0 + 20.0;
+    for l in log {
+        draw_text(l, 10.0, y, 20.0, LIGHTGRAY);
+        y += 20.0;
+    }
+    let cursor = if (get_time() * 2.0) as i64 % 2 == 0 { "_" } else { "" };
+    draw_text(&format!("> {}{}", line, cursor), 10.0, y, 20.0, WHITE);
+}
@@ -0,0 +1,301 @@
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// -------------------------------
+// Action map
+// -------------------------------
+// Every keyboard check outside the dev console and replay viewer goes
+// through here. Each action has up to three keys and always at least one.
+pub const SLOTS: usize = 3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Phase,
+    Restart, // also confirms in menus
+    Menu,    // also backs out of menus
+    Fullscreen,
+}
+
+pub const ACTIONS: [Action; 8] = [
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::Phase,
+    Action::Restart,
+    Action::Menu,
+    Action::Fullscreen,
+];
+
+impl Action {
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move Up",
+            Action::MoveDown => "Move Down",
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::Phase => "Phase",
+            Action::Restart => "Restart / Confirm",
+            Action::Menu => "Menu / Back",
+            Action::Fullscreen => "Fullscreen",
+        }
+    }
+
+    // Key in the save file
+    fn id(self) -> &'static str {
+        match self {
+            Action::MoveUp => "move_up",
+            Action::MoveDown => "move_down",
+            Action::MoveLeft => "move_left",
+            Action::MoveRight => "move_right",
+            Action::Phase => "phase",
+            Action::Restart => "restart",
+            Action::Menu => "menu",
+            Action::Fullscreen => "fullscreen",
+        }
+    }
+
+    fn defaults(self) -> &'static [KeyCode] {
+        match self {
+            Action::MoveUp => &[KeyCode::W, KeyCode::Up],
+            Action::MoveDown => &[KeyCode::S, KeyCode::Down],
+            Action::MoveLeft => &[KeyCode::A, KeyCode::Left],
+            Action::MoveRight => &[KeyCode::D, KeyCode::Right],
+            Action::Phase => &[KeyCode::LeftShift, KeyCode::Space, KeyCode::RightShift],
+            Action::Restart => &[KeyCode::Enter],
+            Action::Menu => &[KeyCode::Escape],
+            Action::Fullscreen => &[KeyCode::F11],
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(from = "BTreeMap<String, Vec<String>>", into = "BTreeMap<String, Vec<String>>")]
+pub struct Bindings {
+    keys: [Vec<KeyCode>; ACTIONS.len()], // indexed by `Action as usize`
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Self { keys: ACTIONS.map(|a| a.defaults().to_vec()) }
+    }
+}
+
+/// What `Bindings::bind` did about a key that was already taken.
+pub enum Rebind {
+    Bound,
+    Swapped(Action, KeyCode), // the other action took this slot's old key
+    Moved(Action),   // the other action simply lost the key
+    Refused(Action), // it was the other action's only key
+    Reserved,
+}
+
+impl Bindings {
+    pub fn keys(&self, a: Action) -> &[KeyCode] {
+        &self.keys[a as usize]
+    }
+
+    pub fn down(&self, a: Action) -> bool {
+        self.keys(a).iter().any(|&k| is_key_down(k))
+    }
+
+    pub fn pressed(&self, a: Action) -> bool {
+        self.keys(a).iter().any(|&k| is_key_pressed(k))
+    }
+
+    /// The primary key's name, for on-screen hints.
+    pub fn label(&self, a: Action) -> String {
+        key_label(self.keys(a)[0])
+    }
+
+    fn owner(&self, key: KeyCode) -> Option<Action> {
+        ACTIONS.into_iter().find(|&a| self.keys(a).contains(&key))
+    }
+
+    /// Puts `key` in `slot` of `a`. A key may only drive one action, so an
+    /// existing owner gives it up, taking this slot's old key in exchange if
+    /// there was one.
+    pub fn bind(&mut self, a: Action, slot: usize, key: KeyCode) -> Rebind {
+        if !is_bindable(key) {
+            return Rebind::Reserved;
+        }
+        let old = self.keys(a).get(slot).copied();
+        let result = match self.owner(key) {
+            Some(other) if other == a => {
+                // Already ours: trade places with whatever this slot holds
+                let keys = &mut self.keys[a as usize];
+                let i = keys.iter().position(|&k| k == key).unwrap_or(0);
+                if slot < keys.len() {
+                    keys.swap(i, slot);
+                } else {
+                    keys.remove(i);
+                    keys.push(key);
+                }
+                return Rebind::Bound;
+            }
+            Some(other) => {
+                let theirs = &mut self.keys[other as usize];
+                let i = theirs.iter().position(|&k| k == key).unwrap_or(0);
+                match old {
+                    Some(old) => {
+                        theirs[i] = old;
+                        Rebind::Swapped(other, old)
+                    }
+                    None if theirs.len() > 1 => {
+                        theirs.remove(i);
+                        Rebind::Moved(other)
+                    }
+                    None => return Rebind::Refused(other),
+                }
+            }
+            None => Rebind::Bound,
+        };
+        let keys = &mut self.keys[a as usize];
+        if slot < keys.len() && old.is_some_and(|o| keys[slot] == o) {
+            keys[slot] = key;
+        } else {
+            keys.insert(slot.min(keys.len()), key);
+        }
+        keys.truncate(SLOTS);
+        result
+    }
+
+    /// Clears a slot, unless it holds the action's last key.
+    pub fn clear(&mut self, a: Action, slot: usize) -> bool {
+        let keys = &mut self.keys[a as usize];
+        if keys.len() > 1 && slot < keys.len() {
+            keys.remove(slot);
+            return true;
+        }
+        false
+    }
+
+    /// Pairs of actions sharing a key. `bind` never creates these, but a
+    /// hand-edited save file can.
+    pub fn conflicts(&self) -> Vec<(KeyCode, Action, Action)> {
+        let mut out = Vec::new();
+        for (i, &a) in ACTIONS.iter().enumerate() {
+            for &b in &ACTIONS[i + 1..] {
+                for &k in self.keys(a) {
+                    if self.keys(b).contains(&k) {
+                        out.push((k, a, b));
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+impl From<BTreeMap<String, Vec<String>>> for Bindings {
+    // Unknown actions and key names are dropped; anything left without a
+    // key falls back to its default.
+    fn from(map: BTreeMap<String, Vec<String>>) -> Self {
+        let mut b = Bindings::default();
+        for a in ACTIONS {
+            let Some(names) = map.get(a.id()) else { continue };
+            let mut keys: Vec<KeyCode> = names.iter().filter_map(|n| parse_key(n)).collect();
+            keys.dedup();
+            keys.truncate(SLOTS);
+            if !keys.is_empty() {
+                b.keys[a as usize] = keys;
+            }
+        }
+        b
+    }
+}
+
+impl From<Bindings> for BTreeMap<String, Vec<String>> {
+    fn from(b: Bindings) -> Self {
+        ACTIONS
+            .into_iter()
+            .map(|a| (a.id().to_string(), b.keys(a).iter().map(|&k| format!("{:?}", k)).collect()))
+            .collect()
+    }
+}
+
+// -------------------------------
+// Key names
+// -------------------------------
+// F3, ` and Backspace stay fixed: overlay, console, and erase/unbind.
+const BINDABLE: &[KeyCode] = &[
+    KeyCode::A, KeyCode::B, KeyCode::C, KeyCode::D, KeyCode::E, KeyCode::F, KeyCode::G,
+    KeyCode::H, KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::M, KeyCode::N,
+    KeyCode::O, KeyCode::P, KeyCode::Q, KeyCode::R, KeyCode::S, KeyCode::T, KeyCode::U,
+    KeyCode::V, KeyCode::W, KeyCode::X, KeyCode::Y, KeyCode::Z,
+    KeyCode::Key0, KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Key5, KeyCode::Key6, KeyCode::Key7, KeyCode::Key8, KeyCode::Key9,
+    KeyCode::Space, KeyCode::Enter, KeyCode::Escape, KeyCode::Tab,
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::Insert, KeyCode::Delete, KeyCode::Home, KeyCode::End,
+    KeyCode::PageUp, KeyCode::PageDown,
+    KeyCode::Apostrophe, KeyCode::Comma, KeyCode::Minus, KeyCode::Period, KeyCode::Slash,
+    KeyCode::Semicolon, KeyCode::Equal, KeyCode::LeftBracket, KeyCode::RightBracket,
+    KeyCode::Backslash,
+    KeyCode::LeftShift, KeyCode::RightShift, KeyCode::LeftControl, KeyCode::RightControl,
+    KeyCode::LeftAlt, KeyCode::RightAlt,
+    KeyCode::F1, KeyCode::F2, KeyCode::F4, KeyCode::F5, KeyCode::F6, KeyCode::F7,
+    KeyCode::F8, KeyCode::F9, KeyCode::F10, KeyCode::F11, KeyCode::F12,
+    KeyCode::Kp0, KeyCode::Kp1, KeyCode::Kp2, KeyCode::Kp3, KeyCode::Kp4,
+    KeyCode::Kp5, KeyCode::Kp6, KeyCode::Kp7, KeyCode::Kp8, KeyCode::Kp9,
+    KeyCode::KpDecimal, KeyCode::KpDivide, KeyCode::KpMultiply, KeyCode::KpSubtract,
+    KeyCode::KpAdd, KeyCode::KpEnter,
+];
+
+pub fn is_bindable(key: KeyCode) -> bool {
+    BINDABLE.contains(&key)
+}
+
+fn parse_key(name: &str) -> Option<KeyCode> {
+    BINDABLE.iter().copied().find(|k| format!("{:?}", k) == name)
+}
+
+/// Short on-screen name, e.g. `W`, `LeftShift`, `5`.
+pub fn key_label(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    match name.strip_prefix("Key") {
+        Some(digit) => digit.to_string(),
+        None => name,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bind_own_key_swaps_slots() {
+        let mut b = Bindings::default();
+        assert!(matches!(b.bind(Action::MoveUp, 0, KeyCode::Up), Rebind::Bound));
+        assert_eq!(b.keys(Action::MoveUp), &[KeyCode::Up, KeyCode::W]);
+        assert!(matches!(b.bind(Action::MoveUp, 0, KeyCode::Up), Rebind::Bound));
+        assert_eq!(b.keys(Action::MoveUp), &[KeyCode::Up, KeyCode::W]);
+    }
+
+    #[test]
+    fn bind_swaps_with_other_action() {
+        let mut b = Bindings::default();
+        let r = b.bind(Action::MoveUp, 0, KeyCode::S);
+        assert!(matches!(r, Rebind::Swapped(Action::MoveDown, KeyCode::W)));
+        assert_eq!(b.keys(Action::MoveUp), &[KeyCode::S, KeyCode::Up]);
+        assert_eq!(b.keys(Action::MoveDown), &[KeyCode::W, KeyCode::Down]);
+        assert!(b.conflicts().is_empty());
+    }
+
+    #[test]
+    fn bind_into_empty_slot() {
+        let mut b = Bindings::default();
+        // Restart has one key; taking Down from MoveDown leaves it with S
+        assert!(matches!(b.bind(Action::Restart, 1, KeyCode::Down), Rebind::Moved(Action::MoveDown)));
+        assert_eq!(b.keys(Action::Restart), &[KeyCode::Enter, KeyCode::Down]);
+        assert_eq!(b.keys(Action::MoveDown), &[KeyCode::S]);
+        // ...but never its last key
+        assert!(matches!(b.bind(Action::Menu, 1, KeyCode::S), Rebind::Refused(Action::MoveDown)));
+        assert!(matches!(b.bind(Action::Menu, 0, KeyCode::LeftSuper), Rebind::Reserved));
+    }
+}
@@ -0,0 +1,110 @@
+use feedback_rush_core::{Event, OrbKind, Vec2};
+use macroquad::prelude::*;
+
+use crate::orb_color;
+
+// -------------------------------
+// Short-lived visual effects
+// -------------------------------
+// Fed from world events; purely cosmetic and ticked by frame time.
+const FLASH_LIFE: f32 = 0.35; // seconds
+const TEXT_LIFE: f32 = 0.9;
+const TEXT_RISE: f32 = 40.0; // pixels over its life
+
+struct Flash {
+    pos: Vec2,
+    age: f32,
+    radius: f32,
+    color: Color,
+}
+
+struct FloatText {
+    pos: Vec2,
+    age: f32,
+    text: String,
+    color: Color,
+}
+
+#[derive(Default)]
+pub struct Effects {
+    flashes: Vec<Flash>,
+    texts: Vec<FloatText>,
+}
+
+impl Effects {
+    pub fn on_event(&mut self, ev: &Event) {
+        match *ev {
+            Event::GhostsMerged { pos } => self.flashes.push(Flash {
+                pos,
+                age: 0.0,
+                radius: 26.0,
+                color: Color::new(0.95, 0.35, 0.35, 0.8),
+            }),
+            Event::GhostsAnnihilated { pos } => self.flashes.push(Flash {
+                pos,
+                age: 0.0,
+                radius: 48.0,
+                color: Color::new(1.0, 0.95, 0.85, 1.0),
+            }),
+            Event::NearMiss { pos, points } => self.text(
+                pos,
+                format!("NEAR MISS +{:.0}", points),
+                Color::new(0.55, 1.0, 0.7, 1.0),
+            ),
+            Event::PhasedThrough { pos, points } => self.text(
+                pos,
+                format!("PHASED +{:.0}", points),
+                Color::new(0.45, 0.9, 0.95, 1.0),
+            ),
+            Event::ChainCompleted { pos, points } => {
+                self.text(pos, format!("CHAIN +{:.0}", points), YELLOW);
+                self.flashes.push(Flash { pos, age: 0.0, radius: 40.0, color: YELLOW });
+            }
+            Event::ChainBroken { pos } => self.text(pos, "CHAIN BROKEN".to_string(), GRAY),
+            Event::PowerUp { kind, pos } => {
+                let label = match kind {
+                    OrbKind::PhaseRefill => "PHASE FULL",
+                    OrbKind::SlowTime => "SLOW TIME",
+                    OrbKind::Purge => "PURGE",
+                    OrbKind::Multiplier => "x2 SCORE",
+                    OrbKind::Decoy => "DECOY!",
+                    OrbKind::Score => return,
+                };
+                self.text(pos, label.to_string(), orb_color(kind));
+            }
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, pos: Vec2, text: String, color: Color) {
+        self.texts.push(FloatText { pos, age: 0.0, text, color });
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for f in &mut self.flashes {
+            f.age += dt;
+        }
+        self.flashes.retain(|f| f.age < FLASH_LIFE);
+        for t in &mut self.texts {
+            t.age += dt;
+        }
+        self.texts.retain(|t| t.age < TEXT_LIFE);
+    }
+
+    pub fn draw(&self, off: Vec2) {
+        for f in &self.flashes {
+            let k = f.age / FLASH_LIFE;
+            let a = f.color.a * (1.0 - k);
+            let (x, y) = (f.pos.x + off.x, f.pos.y + off.y);
+            draw_circle(x, y, f.radius * (0.4 + 0.6 * k), Color { a: a * 0.35, ..f.color });
+            draw_circle_lines(x, y, f.radius * (0.5 + k), 3.0, Color { a, ..f.color });
+        }
+        for t in &self.texts {
+            let k = t.age / TEXT_LIFE;
+            let d = measure_text(&t.text, None, 20, 1.0);
+            let x = t.pos.x + off.x - d.width * 0.5;
+            let y = t.pos.y + off.y - 18.0 - TEXT_RISE * k;
+            draw_text(&t.text, x, y, 20.0, Color { a: 1.0 - k * k, ..t.color });
+        }
+    }
+}
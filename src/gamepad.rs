@@ -0,0 +1,150 @@
+use macroquad::prelude::*;
+
+use crate::input::MenuInput;
+
+// -------------------------------
+// Gamepads
+// -------------------------------
+// Backed by gilrs when built with the `gamepad` feature; otherwise every pad
+// reads as idle. The most recently used pad drives play and the menus.
+#[cfg(feature = "gamepad")]
+const DEADZONE: f32 = 0.2; // radial, on the left stick
+#[cfg(feature = "gamepad")]
+const TRIGGER_PRESS: f32 = 0.3;
+const MENU_TILT: f32 = 0.6; // stick deflection that counts as a menu press
+
+/// One frame of the active pad, already deadzoned.
+#[derive(Clone, Copy, Default)]
+pub struct PadState {
+    pub stick: Vec2, // left stick or d-pad, y down, length <= 1
+    pub phase: bool, // right trigger or south/west face button
+    pub confirm: bool,
+    pub back: bool,
+    pub start: bool,
+}
+
+pub struct Gamepads {
+    #[cfg(feature = "gamepad")]
+    gilrs: Option<gilrs::Gilrs>,
+    #[cfg(feature = "gamepad")]
+    active: Option<gilrs::GamepadId>,
+    cur: PadState,
+    prev: PadState,
+}
+
+impl Gamepads {
+    #[cfg(feature = "gamepad")]
+    pub fn new() -> Self {
+        let gilrs = gilrs::Gilrs::new()
+            .map_err(|e| eprintln!("gamepad support unavailable: {}", e))
+            .ok();
+        let active = gilrs.as_ref().and_then(|g| g.gamepads().next().map(|(id, _)| id));
+        Self { gilrs, active, cur: PadState::default(), prev: PadState::default() }
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    pub fn new() -> Self {
+        Self { cur: PadState::default(), prev: PadState::default() }
+    }
+
+    /// Shows the frame, then reads the pads for the next one. Every scene
+    /// ends its frames here, so pads are read exactly once per frame and no
+    /// press is lost to a second read. Returns a message when a pad is
+    /// plugged in or removed.
+    pub async fn next_frame(&mut self) -> Option<String> {
+        next_frame().await;
+        self.update()
+    }
+
+    /// Drains pad events.
+    #[cfg(feature = "gamepad")]
+    fn update(&mut self) -> Option<String> {
+        use gilrs::{Axis, Button, EventType};
+
+        self.prev = self.cur;
+        let g = self.gilrs.as_mut()?;
+        let mut msg = None;
+        while let Some(ev) = g.next_event() {
+            match ev.event {
+                EventType::Connected => {
+                    self.active = Some(ev.id);
+                    msg = Some(format!("Gamepad connected: {}", g.gamepad(ev.id).name()));
+                }
+                EventType::Disconnected => {
+                    if self.active == Some(ev.id) {
+                        self.active = g.gamepads().next().map(|(id, _)| id);
+                    }
+                    msg = Some("Gamepad disconnected".to_string());
+                }
+                EventType::Dropped => {}
+                _ => self.active = Some(ev.id),
+            }
+        }
+
+        let Some(pad) = self.active.map(|id| g.gamepad(id)) else {
+            self.cur = PadState::default();
+            return msg;
+        };
+        let mut stick = radial_deadzone(vec2(
+            pad.value(Axis::LeftStickX),
+            -pad.value(Axis::LeftStickY),
+        ));
+        let held = |b| if pad.is_pressed(b) { 1.0 } else { 0.0 };
+        let dpad = vec2(
+            held(Button::DPadRight) - held(Button::DPadLeft),
+            held(Button::DPadDown) - held(Button::DPadUp),
+        );
+        if dpad != Vec2::ZERO {
+            stick = dpad.normalize();
+        }
+        let trigger = pad.button_data(Button::RightTrigger2).map_or(0.0, |d| d.value());
+        self.cur = PadState {
+            stick,
+            phase: trigger > TRIGGER_PRESS
+                || pad.is_pressed(Button::South)
+                || pad.is_pressed(Button::West),
+            confirm: pad.is_pressed(Button::South),
+            back: pad.is_pressed(Button::East),
+            start: pad.is_pressed(Button::Start),
+        };
+        msg
+    }
+
+    #[cfg(not(feature = "gamepad"))]
+    fn update(&mut self) -> Option<String> {
+        None
+    }
+
+    pub fn state(&self) -> PadState {
+        self.cur
+    }
+
+    /// Start went down this frame; pauses and resumes play.
+    pub fn start_pressed(&self) -> bool {
+        self.cur.start && !self.prev.start
+    }
+
+    /// Buttons and stick flicks that went down this frame, for menus.
+    pub fn pressed(&self) -> MenuInput {
+        let (c, p) = (self.cur, self.prev);
+        let edge = |now: bool, before: bool| now && !before;
+        MenuInput {
+            up: edge(c.stick.y < -MENU_TILT, p.stick.y < -MENU_TILT),
+            down: edge(c.stick.y > MENU_TILT, p.stick.y > MENU_TILT),
+            left: edge(c.stick.x < -MENU_TILT, p.stick.x < -MENU_TILT),
+            right: edge(c.stick.x > MENU_TILT, p.stick.x > MENU_TILT),
+            confirm: edge(c.confirm, p.confirm) || edge(c.start, p.start),
+            back: edge(c.back, p.back),
+        }
+    }
+}
+
+/// Zero inside the deadzone, then rescaled so output still spans 0..1.
+#[cfg(feature = "gamepad")]
+fn radial_deadzone(v: Vec2) -> Vec2 {
+    let len = v.length();
+    if len < DEADZONE {
+        return Vec2::ZERO;
+    }
+    v / len * ((len - DEADZONE) / (1.0 - DEADZONE)).min(1.0)
+}
@@ -0,0 +1,64 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::save;
+
+// -------------------------------
+// Tuning file watcher
+// -------------------------------
+// Polls `tuning.toml` (working directory first, then the config directory)
+// for changes. No file means no overrides.
+const TUNING_FILE: &str = "tuning.toml";
+const POLL_INTERVAL: f64 = 0.5; // seconds
+
+pub struct TuningWatcher {
+    stamp: Option<(PathBuf, SystemTime)>,
+    next_poll: f64,
+}
+
+impl TuningWatcher {
+    pub fn new() -> Self {
+        Self { stamp: None, next_poll: 0.0 }
+    }
+
+    fn stamp(path: &Path) -> Option<(PathBuf, SystemTime)> {
+        Some((path.to_path_buf(), fs::metadata(path).ok()?.modified().ok()?))
+    }
+
+    fn locate() -> Option<PathBuf> {
+        let local = PathBuf::from(TUNING_FILE);
+        if local.is_file() {
+            return Some(local);
+        }
+        save::app_dir().map(|d| d.join(TUNING_FILE)).filter(|p| p.is_file())
+    }
+
+    /// The file's current contents regardless of whether it changed, for
+    /// applying at the start of a run. Also resets change tracking.
+    pub fn current(&mut self) -> Option<String> {
+        let path = Self::locate();
+        self.stamp = path.as_deref().and_then(Self::stamp);
+        fs::read_to_string(path?).ok()
+    }
+
+    /// New contents if the file changed since the last call, at most every
+    /// `POLL_INTERVAL` seconds. Deleting the file reports an empty patch.
+    pub fn poll(&mut self, now: f64) -> Option<String> {
+        if now < self.next_poll {
+            return None;
+        }
+        self.next_poll = now + POLL_INTERVAL;
+
+        let path = Self::locate();
+        let stamp = path.as_deref().and_then(Self::stamp);
+        if stamp == self.stamp {
+            return None;
+        }
+        self.stamp = stamp;
+        match path {
+            Some(p) => fs::read_to_string(p).ok(),
+            None => Some(String::new()),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use feedback_rush_core::{InputSource, TickInput, World};
+use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::controls::{Action, Bindings};
+use crate::gamepad::{Gamepads, PadState};
+use crate::view::to_arena;
+
+// -------------------------------
+// Keyboard (through the action map) + gamepad
+// -------------------------------
+// Pointer steering: full acceleration once the target is this far away,
+// proportionally less inside it so the player settles instead of orbiting
+const FOLLOW_RANGE: f32 = 60.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ControlScheme {
+    #[default]
+    Keys,
+    Pointer, // steer toward the mouse or a touch
+}
+
+impl ControlScheme {
+    pub fn toggled(self) -> Self {
+        match self {
+            ControlScheme::Keys => ControlScheme::Pointer,
+            ControlScheme::Pointer => ControlScheme::Keys,
+        }
+    }
+}
+
+/// The local player. `pad` is refreshed from `Gamepads` once per frame.
+pub struct LocalInput {
+    pub bindings: Bindings,
+    pub scheme: ControlScheme,
+    pub pad: PadState,
+}
+
+impl InputSource for LocalInput {
+    fn next_input(&mut self, world: &World) -> Option<TickInput> {
+        let b = &self.bindings;
+        let mut dir = vec2(0.0, 0.0);
+        if b.down(Action::MoveLeft) {
+            dir.x -= 1.0;
+        }
+        if b.down(Action::MoveRight) {
+            dir.x += 1.0;
+        }
+        if b.down(Action::MoveUp) {
+            dir.y -= 1.0;
+        }
+        if b.down(Action::MoveDown) {
+            dir.y += 1.0;
+        }
+        // The stick is analog: a half tilt is half the acceleration
+        dir += self.pad.stick;
+        // Keys and pad still win while held, so the schemes can be mixed
+        let mut pointer_phase = false;
+        if self.scheme == ControlScheme::Pointer && dir == Vec2::ZERO {
+            let p = pointer(world.arena);
+            let to = p.target - world.player.pos;
+            if to.length() > world.player.radius * 0.5 {
+                dir = to / FOLLOW_RANGE;
+            }
+            pointer_phase = p.phase;
+        }
+        if dir.length_squared() > 1.0 {
+            dir = dir.normalize();
+        }
+
+        let want_phase = b.down(Action::Phase) || self.pad.phase || pointer_phase;
+
+        Some(TickInput { dir, want_phase })
+    }
+}
+
+pub struct Pointer {
+    pub target: Vec2,
+    pub phase: bool,
+}
+
+/// Where the pointer scheme is steering, in arena coordinates: the first
+/// touch if there is one (a second finger phases), otherwise the mouse
+/// (right button phases). After a touch ends the mouse position stays where
+/// it lifted.
+pub fn pointer(arena: Vec2) -> Pointer {
+    let touches = touches();
+    if let Some(t) = touches.first() {
+        return Pointer { target: to_arena(arena, t.position), phase: touches.len() >= 2 };
+    }
+    let (x, y) = mouse_position();
+    Pointer {
+        target: to_arena(arena, vec2(x, y)),
+        phase: is_mouse_button_down(MouseButton::Right),
+    }
+}
+
+// -------------------------------
+// Menu navigation
+// -------------------------------
+/// Edge-triggered menu actions from the keyboard and the active gamepad.
+#[derive(Clone, Copy, Default)]
+pub struct MenuInput {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub confirm: bool,
+    pub back: bool,
+}
+
+impl MenuInput {
+    pub fn poll(pads: &Gamepads, b: &Bindings) -> Self {
+        let p = pads.pressed();
+        Self {
+            up: b.pressed(Action::MoveUp) || p.up,
+            down: b.pressed(Action::MoveDown) || p.down,
+            left: b.pressed(Action::MoveLeft) || p.left,
+            right: b.pressed(Action::MoveRight) || p.right,
+            confirm: b.pressed(Action::Restart) || p.confirm,
+            back: b.pressed(Action::Menu) || p.back,
+        }
+    }
+}
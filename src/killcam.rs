@@ -0,0 +1,138 @@
+use std::collections::VecDeque;
+
+use feedback_rush_core::{Ghost, GhostKind, World};
+use macroquad::prelude::*;
+
+use crate::controls::{Action, Bindings};
+use crate::fx::Effects;
+use crate::gamepad::Gamepads;
+use crate::input::MenuInput;
+use crate::view::arena_camera;
+use crate::{camera_offset, draw_world, Settings};
+
+// -------------------------------
+// Kill-cam
+// -------------------------------
+// A rolling buffer of world snapshots, played back slowed down after a ghost
+// ends the run, with the killer singled out.
+const SECONDS: f32 = 2.5; // how far back it rewinds
+const INTERVAL: f32 = 1.0 / 30.0; // seconds between snapshots
+const SPEED: f32 = 0.35;
+const HOLD: f32 = 1.2; // seconds on the final frame
+
+pub struct KillCam {
+    frames: VecDeque<World>,
+}
+
+impl KillCam {
+    pub fn new() -> Self {
+        Self { frames: VecDeque::new() }
+    }
+
+    fn capacity() -> usize {
+        (SECONDS / INTERVAL) as usize
+    }
+
+    /// Call after every tick; `last` forces a snapshot of the fatal one.
+    pub fn record(&mut self, w: &World, last: bool) {
+        let stride = w.ticks(INTERVAL).max(1) as u64;
+        if !w.tick.is_multiple_of(stride) && !last {
+            return;
+        }
+        if self.frames.len() >= Self::capacity() {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(w.snapshot());
+    }
+
+    /// Plays the buffer through once. Confirm or back skips it.
+    pub async fn play(
+        &self,
+        killer: u32,
+        settings: &Settings,
+        bindings: &Bindings,
+        pads: &mut Gamepads,
+    ) {
+        let Some(last) = self.frames.len().checked_sub(1) else {
+            return;
+        };
+        let frame_time = INTERVAL / SPEED;
+        let fx = Effects::default();
+        let mut t = 0.0f32;
+        loop {
+            let nav = MenuInput::poll(pads, bindings);
+            if nav.confirm || nav.back {
+                return;
+            }
+            let i = ((t / frame_time) as usize).min(last);
+            if i == last && t > last as f32 * frame_time + HOLD {
+                return;
+            }
+            let w = &self.frames[i];
+            draw_world(w, settings, &fx, 1.0);
+            if let Some(g) = w.ghosts.iter().find(|g| g.id == killer) {
+                draw_killer(w, g, settings, i == last);
+            }
+            draw_banner(w.ghosts.iter().find(|g| g.id == killer), bindings);
+            t += get_frame_time();
+            pads.next_frame().await;
+        }
+    }
+}
+
+/// The killer's whole path, where it is on it, and a ring around it.
+fn draw_killer(w: &World, g: &Ghost, settings: &Settings, hit: bool) {
+    let off = camera_offset(w, settings);
+    set_camera(&arena_camera(w.arena));
+    let path = Color::new(1.0, 0.9, 0.3, 0.5);
+    for pair in g.samples.windows(2) {
+        let (a, b) = (pair[0].pos + g.offset + off, pair[1].pos + g.offset + off);
+        draw_line(a.x, a.y, b.x, b.y, 1.5, path);
+    }
+    let p = g.current_pos() + off;
+    let pulse = 4.0 + 3.0 * (get_time() as f32 * 10.0).sin().abs();
+    draw_circle_lines(p.x, p.y, g.radius + pulse, 2.5, Color::new(1.0, 0.9, 0.3, 1.0));
+    if hit {
+        let q = w.player.pos + off;
+        draw_circle_lines(q.x, q.y, w.player.radius + 6.0, 2.0, WHITE);
+    }
+    set_default_camera();
+}
+
+fn draw_banner(killer: Option<&Ghost>, b: &Bindings) {
+    let sw = screen_width();
+    let sh = screen_height();
+    let title = "KILL CAM";
+    let td = measure_text(title, None, 36, 1.0);
+    draw_text(title, (sw - td.width) * 0.5, 100.0, 36.0, Color::new(1.0, 0.9, 0.3, 1.0));
+    if let Some(g) = killer {
+        let s = format!("Killed by {} ghost, {}", article(g.kind), loop_position(g));
+        let d = measure_text(&s, None, 24, 1.0);
+        draw_text(&s, (sw - d.width) * 0.5, 130.0, 24.0, WHITE);
+    }
+    let hint = format!("{} / {}: Skip", b.label(Action::Restart), b.label(Action::Menu));
+    let hd = measure_text(&hint, None, 20, 1.0);
+    draw_text(&hint, (sw - hd.width) * 0.5, sh - 40.0, 20.0, GRAY);
+}
+
+fn article(kind: GhostKind) -> String {
+    let name = kind.name().replace('_', "-");
+    if kind == GhostKind::Echo { format!("an {}", name) } else { format!("a {}", name) }
+}
+
+/// Where in its recording the ghost was, in seconds.
+fn loop_position(g: &Ghost) -> String {
+    let len = g.duration().max(0.01);
+    match g.kind {
+        GhostKind::Shadow => {
+            let head = g.samples.last().map_or(0.0, |f| f.t);
+            format!("trailing you by {:.1}s", head - g.progress)
+        }
+        GhostKind::PingPong => {
+            let q = g.progress.rem_euclid(2.0 * len);
+            let (at, dir) = if q > len { (2.0 * len - q, "back") } else { (q, "out") };
+            format!("{:.1}s of {:.1}s into its loop, heading {}", at, len, dir)
+        }
+        _ => format!("{:.1}s of {:.1}s into its loop", g.progress.rem_euclid(len), len),
+    }
+}
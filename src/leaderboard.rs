@@ -0,0 +1,223 @@
+use feedback_rush_core::{World, TICK_RATE};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// -------------------------------
+// Local top-N tables, one per mode and tick rate
+// -------------------------------
+pub const TABLE_SIZE: usize = 10;
+pub const INITIALS_LEN: usize = 3;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Entry {
+    pub initials: String,
+    pub score: f32,
+    pub time_alive: f32,
+    pub peak_combo: f32,
+    pub orbs_collected: u32,
+    pub ghosts_spawned: u32,
+    pub near_misses: u32,
+    pub phase_throughs: u32,
+    pub date: String, // YYYY-MM-DD, UTC
+    pub seed: u64,
+    pub tick_rate: u32, // entries from before tick rates could be picked ran at the default
+}
+
+impl Default for Entry {
+    fn default() -> Self {
+        Entry {
+            initials: String::new(),
+            score: 0.0,
+            time_alive: 0.0,
+            peak_combo: 0.0,
+            orbs_collected: 0,
+            ghosts_spawned: 0,
+            near_misses: 0,
+            phase_throughs: 0,
+            date: String::new(),
+            seed: 0,
+            tick_rate: TICK_RATE,
+        }
+    }
+}
+
+impl Entry {
+    /// A finished run, waiting for initials.
+    pub fn from_world(w: &World) -> Self {
+        Entry {
+            initials: String::new(),
+            score: w.score,
+            time_alive: w.time_alive,
+            peak_combo: w.stats.peak_combo,
+            orbs_collected: w.stats.orbs_collected,
+            ghosts_spawned: w.stats.ghosts_spawned,
+            near_misses: w.stats.near_misses,
+            phase_throughs: w.stats.phase_throughs,
+            date: today(),
+            seed: w.seed,
+            tick_rate: w.tick_rate,
+        }
+    }
+}
+
+/// Tables keyed by mode id, each sorted best first. A mode's runs at
+/// different tick rates share its table but are ranked apart, `TABLE_SIZE`
+/// of each, so a faster rate never pushes slower runs off the board.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Leaderboard {
+    tables: BTreeMap<String, Vec<Entry>>,
+}
+
+impl Leaderboard {
+    pub fn top(&self, mode: &str, hz: u32) -> Vec<&Entry> {
+        let table = self.tables.get(mode).map(|t| t.as_slice()).unwrap_or(&[]);
+        table.iter().filter(|e| e.tick_rate == hz).collect()
+    }
+
+    pub fn best(&self, mode: &str, hz: u32) -> f32 {
+        self.top(mode, hz).first().map(|e| e.score).unwrap_or(0.0)
+    }
+
+    pub fn qualifies(&self, mode: &str, hz: u32, score: f32) -> bool {
+        let t = self.top(mode, hz);
+        score > 0.0 && (t.len() < TABLE_SIZE || t.last().is_some_and(|e| score > e.score))
+    }
+
+    /// Inserts among runs at the entry's tick rate and returns the 0-based
+    /// rank, or `None` if it didn't place.
+    pub fn insert(&mut self, mode: &str, entry: Entry) -> Option<usize> {
+        let table = self.tables.entry(mode.to_string()).or_default();
+        let ahead = |e: &&Entry| e.tick_rate == entry.tick_rate && e.score >= entry.score;
+        let rank = table.iter().filter(ahead).count();
+        if rank >= TABLE_SIZE {
+            return None;
+        }
+        let at = table.iter().position(|e| entry.score > e.score).unwrap_or(table.len());
+        table.insert(at, entry);
+        trim(table);
+        Some(rank)
+    }
+
+    /// Re-sorts and trims after loading, in case the file was edited by hand.
+    pub fn normalize(&mut self) {
+        for table in self.tables.values_mut() {
+            table.retain(|e| e.score.is_finite());
+            table.sort_by(|a, b| b.score.total_cmp(&a.score));
+            trim(table);
+        }
+    }
+}
+
+/// Keeps the best `TABLE_SIZE` of each tick rate in a sorted table.
+fn trim(table: &mut Vec<Entry>) {
+    let mut kept: BTreeMap<u32, usize> = BTreeMap::new();
+    table.retain(|e| {
+        let n = kept.entry(e.tick_rate).or_default();
+        *n += 1;
+        *n <= TABLE_SIZE
+    });
+}
+
+/// Today's date (UTC) as YYYY-MM-DD.
+fn today() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let (y, m, d) = civil_from_days((secs / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", y, m, d)
+}
+
+// Howard Hinnant's days-since-epoch to proleptic Gregorian date
+fn civil_from_days(z: i64) -> (i64, u32, u32) {
+    let z = z + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(score: f32, tick_rate: u32) -> Entry {
+        Entry { score, tick_rate, ..Default::default() }
+    }
+
+    #[test]
+    fn tick_rates_rank_apart() {
+        let mut b = Leaderboard::default();
+        for i in 0..TABLE_SIZE {
+            b.insert("classic", run(100.0 + i as f32, 60));
+        }
+        // A full 60 Hz table leaves room at 240 Hz, and is untouched by it
+        assert!(!b.qualifies("classic", 60, 50.0));
+        assert!(b.qualifies("classic", 240, 50.0));
+        assert_eq!(b.insert("classic", run(500.0, 240)), Some(0));
+        assert_eq!(b.top("classic", 60).len(), TABLE_SIZE);
+        assert_eq!(b.best("classic", 60), 109.0);
+        assert_eq!(b.best("classic", 240), 500.0);
+        assert_eq!(b.insert("classic", run(105.5, 60)), Some(4));
+        assert_eq!(b.top("classic", 60).last().map(|e| e.score), Some(101.0));
+    }
+
+    #[test]
+    fn insert_ranks_and_keeps_the_top_ten() {
+        let mut b = Leaderboard::default();
+        assert_eq!(b.insert("classic", run(10.0, 60)), Some(0));
+        assert_eq!(b.insert("classic", run(30.0, 60)), Some(0));
+        assert_eq!(b.insert("classic", run(20.0, 60)), Some(1));
+        // A tie goes below the run already there
+        assert_eq!(b.insert("classic", run(20.0, 60)), Some(2));
+        for i in 0..TABLE_SIZE {
+            b.insert("classic", run(100.0 + i as f32, 60));
+        }
+        let scores: Vec<f32> = b.top("classic", 60).iter().map(|e| e.score).collect();
+        assert_eq!(scores.len(), TABLE_SIZE);
+        assert_eq!((scores[0], scores[TABLE_SIZE - 1]), (109.0, 100.0));
+        assert!(scores.windows(2).all(|w| w[0] >= w[1]));
+        assert_eq!(b.insert("classic", run(50.0, 60)), None);
+        assert!(b.top("nightmare", 60).is_empty());
+    }
+
+    #[test]
+    fn qualifies_needs_a_positive_score_that_beats_the_last() {
+        let mut b = Leaderboard::default();
+        assert!(!b.qualifies("classic", 60, 0.0));
+        assert!(b.qualifies("classic", 60, 1.0));
+        for i in 0..TABLE_SIZE {
+            b.insert("classic", run(10.0 + i as f32, 60));
+        }
+        assert!(!b.qualifies("classic", 60, 10.0));
+        assert!(b.qualifies("classic", 60, 10.5));
+    }
+
+    #[test]
+    fn normalize_cleans_hand_edited_tables() {
+        let mut b = Leaderboard::default();
+        let mut table = vec![run(f32::NAN, 60), run(5.0, 60)];
+        table.extend((0..TABLE_SIZE).map(|i| run(i as f32 + 10.0, 60)));
+        b.tables.insert("classic".to_string(), table);
+        b.normalize();
+        let scores: Vec<f32> = b.top("classic", 60).iter().map(|e| e.score).collect();
+        let want: Vec<f32> = (0..TABLE_SIZE).rev().map(|i| i as f32 + 10.0).collect();
+        assert_eq!(scores, want);
+    }
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11_017), (2000, 3, 1));
+        assert_eq!(civil_from_days(19_782), (2024, 2, 29));
+        assert_eq!(civil_from_days(47_541), (2100, 3, 1));
+    }
+}
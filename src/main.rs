@@ -1,100 +1,58 @@
+use feedback_rush_core::util::{interpolate, lerp};
+use feedback_rush_core::mode::{load_dir, merge_modes};
+use feedback_rush_core::{
+    builtin_modes, rng, step, Event, GhostKind, InputSource, ModeConfig, Obstacle, OrbKind, Replay,
+    Shape, World, ARENA, TICK_RATE, TICK_RATES,
+};
 use macroquad::audio::{
     load_sound_from_bytes, play_sound, stop_sound, PlaySoundParams, Sound,
 };
 use macroquad::prelude::*;
-use std::collections::VecDeque;
+use serde::{Deserialize, Serialize};
+
+mod console;
+mod controls;
+mod fx;
+mod gamepad;
+mod hotreload;
+mod input;
+mod killcam;
+mod leaderboard;
+mod pacing;
+mod replays;
+mod save;
+mod view;
+
+use console::DevConsole;
+use controls::{Action, Bindings, Rebind, ACTIONS, SLOTS};
+use fx::Effects;
+use gamepad::Gamepads;
+use hotreload::TuningWatcher;
+use input::{ControlScheme, LocalInput, MenuInput};
+use killcam::KillCam;
+use leaderboard::{Entry, Leaderboard, INITIALS_LEN};
+use pacing::Pacing;
 
 // -------------------------------
 // Config
 // -------------------------------
-// Initial window size; all gameplay adapts to current window size at runtime
+// Initial window size; the arena is scaled to fit whatever the window becomes
 const WIDTH: f32 = 960.0;
 const HEIGHT: f32 = 540.0;
 
-const PLAYER_RADIUS: f32 = 12.0;
-const GHOST_RADIUS: f32 = 10.0;
-const ORB_RADIUS: f32 = 8.0;
-
-const ACCEL: f32 = 1600.0;
-const FRICTION: f32 = 5.5;
-const MAX_SPEED: f32 = 300.0;
-
-const FIXED_DT: f32 = 1.0 / 60.0;
-const INPUT_HISTORY_SECONDS: f32 = 12.0;
-
-const PHASE_MAX: f32 = 1.5;
-const PHASE_DRAIN: f32 = 1.6; // per second
-const PHASE_REGEN: f32 = 0.6; // per second
-
-const ORB_SPAWN_BASE: f32 = 1.5; // seconds between spawns at start
-const ORB_SPAWN_MIN: f32 = 0.35; // fastest spawn
-const ORB_SAFE_RADIUS: f32 = 80.0; // avoid spawning on top of the player
-
-const COMBO_DECAY_PER_SEC: f32 = 0.25;
-
-// -------------------------------
-// Game Data
-// -------------------------------
-#[derive(Clone, Copy, Debug)]
-struct InputFrame {
-    pos: Vec2,
-}
-
-#[derive(Clone)]
-struct Ghost {
-    samples: Vec<Vec2>,
-    progress: f32, // measured in "frames"
-    speed: f32,    // frames per second (1.0 == 60fps playback)
-    radius: f32,
-    color: Color,
-    ttl: f32, // seconds to live
-}
-
-impl Ghost {
-    fn current_pos(&self) -> Vec2 {
-        if self.samples.is_empty() {
-            return vec2(0.0, 0.0);
-        }
-        let n = self.samples.len() as f32;
-        let mut p = self.progress % n.max(1.0);
-        if p < 0.0 {
-            p += n;
-        }
-        let i0 = p.floor() as usize;
-        let i1 = (i0 + 1) % self.samples.len();
-        let t = p.fract();
-        self.samples[i0].lerp(self.samples[i1], t)
-    }
-}
+const GHOST_COLOR: Color = Color::new(0.95, 0.25, 0.25, 0.9);
+const ORB_FADE: f32 = 1.5; // seconds an expiring orb takes to fade out
 
-struct Orb {
-    pos: Vec2,
-    radius: f32,
-    alive: bool,
-}
+const REPLAY_SPEEDS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
 
-// -------------------------------
-// Player
-// -------------------------------
-struct Player {
-    pos: Vec2,
-    vel: Vec2,
-    radius: f32,
-    phase_energy: f32,
-    phase_active: bool,
-}
+// macroquad 0.4 swallows miniquad's focus events (its handler doesn't pass
+// them to input subscribers), so plain focus loss can't be seen. What can:
+// a frame this long (window hidden, minimised or dragged), or a zero-size
+// window, is taken to mean the player looked away
+const STALL_GAP: f32 = 0.5; // seconds
 
-impl Player {
-    fn new(pos: Vec2) -> Self {
-        Self {
-            pos,
-            vel: vec2(0.0, 0.0),
-            radius: PLAYER_RADIUS,
-            phase_energy: PHASE_MAX,
-            phase_active: false,
-        }
-    }
-}
+// Extra mode files, checked after the built-ins (later wins on equal ids)
+const MODES_DIR: &str = "modes";
 
 // -------------------------------
 // Sounds: tiny procedural WAVs
@@ -155,141 +113,24 @@ fn tone_wav(freq: f32, dur_s: f32, vol: f32, attack_s: f32, release_s: f32) -> V
 struct AudioSet {
     collect: Sound,
     ghost_spawn: Sound,
+    pop: Sound,   // ghosts colliding
+    close: Sound, // near miss or phase-through
+    power: Sound, // power-up orb
+    decoy: Sound,
     death: Sound,
     drone: Sound,
 }
 
-// -------------------------------
-// Utils
-// -------------------------------
-fn clamp_rect(p: Vec2, r: f32, w: f32, h: f32) -> (Vec2, Vec2) {
-    let mut pos = p;
-    let mut norm = vec2(0.0, 0.0);
-    if pos.x - r < 0.0 {
-        pos.x = r;
-        norm.x = 1.0;
-    }
-    if pos.x + r > w {
-        pos.x = w - r;
-        norm.x = -1.0;
-    }
-    if pos.y - r < 0.0 {
-        pos.y = r;
-        norm.y = 1.0;
-    }
-    if pos.y + r > h {
-        pos.y = h - r;
-        norm.y = -1.0;
-    }
-    (pos, norm)
-}
-
-fn circle_overlap(a: Vec2, ar: f32, b: Vec2, br: f32) -> bool {
-    a.distance_squared(b) <= (ar + br) * (ar + br)
-}
-
-fn rand_pos_away_from(p: Vec2, min_dist: f32, w: f32, h: f32) -> Vec2 {
-    use macroquad::rand::gen_range;
-    for _ in 0..64 {
-        let w1 = (w - 40.0).max(41.0);
-        let h1 = (h - 40.0).max(41.0);
-        let rp = vec2(gen_range(40.0, w1), gen_range(40.0, h1));
-        if rp.distance(p) >= min_dist {
-            return rp;
-        }
-    }
-    vec2(
-        clamp(p.x + 200.0, 40.0, (w - 40.0).max(40.0)),
-        clamp(p.y + 150.0, 40.0, (h - 40.0).max(40.0)),
-    )
-}
-
-fn lerp(a: f32, b: f32, t: f32) -> f32 {
-    a + (b - a) * t
-}
-
-// -------------------------------
-// Game State
-// -------------------------------
-#[derive(Clone, Copy)]
-enum GameMode {
-    Classic,
-    TimeAttack,
-    Nightmare,
-}
-
-impl GameMode {
-    fn all() -> &'static [GameMode] {
-        &[GameMode::Classic, GameMode::TimeAttack, GameMode::Nightmare]
-    }
-    fn name(&self) -> &'static str {
-        match self {
-            GameMode::Classic => "Classic",
-            GameMode::TimeAttack => "Time Attack",
-            GameMode::Nightmare => "Nightmare",
-        }
-    }
-    fn index(&self) -> usize {
-        match self {
-            GameMode::Classic => 0,
-            GameMode::TimeAttack => 1,
-            GameMode::Nightmare => 2,
-        }
-    }
-    fn from_index(i: usize) -> GameMode {
-        match i % 3 {
-            0 => GameMode::Classic,
-            1 => GameMode::TimeAttack,
-            _ => GameMode::Nightmare,
-        }
-    }
-}
-
-struct ModeConfig {
-    time_limit: Option<f32>,
-    ghost_speed_mul: f32,
-    difficulty_rate: f32,
-    spawn_rate_mul: f32,
-    ghost_flicker: bool,
-    ghost_invisible_far: bool,
-}
-
-fn mode_config(mode: GameMode) -> ModeConfig {
-    match mode {
-        GameMode::Classic => ModeConfig {
-            time_limit: None,
-            ghost_speed_mul: 1.0,
-            difficulty_rate: 0.2,
-            spawn_rate_mul: 1.0,
-            ghost_flicker: false,
-            ghost_invisible_far: false,
-        },
-        GameMode::TimeAttack => ModeConfig {
-            time_limit: Some(60.0),
-            ghost_speed_mul: 1.1,
-            difficulty_rate: 0.28,
-            spawn_rate_mul: 1.2,
-            ghost_flicker: false,
-            ghost_invisible_far: false,
-        },
-        GameMode::Nightmare => ModeConfig {
-            time_limit: None,
-            ghost_speed_mul: 1.25,
-            difficulty_rate: 0.32,
-            spawn_rate_mul: 1.35,
-            ghost_flicker: true,
-            ghost_invisible_far: true,
-        },
-    }
-}
-
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
 struct Settings {
     audio_enabled: bool,
     master_volume: f32,
     shake_enabled: bool,
     vignette: f32, // 0..1
     fullscreen: bool,
+    control_scheme: ControlScheme,
+    tick_rate: u32, // one of TICK_RATES; applies from the next run
 }
 
 impl Default for Settings {
@@ -300,123 +141,58 @@ impl Default for Settings {
             shake_enabled: true,
             vignette: 0.6,
             fullscreen: false,
+            control_scheme: ControlScheme::Keys,
+            tick_rate: TICK_RATE,
         }
     }
 }
 
+impl Settings {
+    /// Clamp anything a hand-edited save file could have put out of range.
+    fn sanitized(mut self) -> Self {
+        let unit = |v: f32, fallback: f32| if v.is_finite() { v.clamp(0.0, 1.0) } else { fallback };
+        let d = Settings::default();
+        self.master_volume = unit(self.master_volume, d.master_volume);
+        self.vignette = unit(self.vignette, d.vignette);
+        if !TICK_RATES.contains(&self.tick_rate) {
+            self.tick_rate = d.tick_rate;
+        }
+        self
+    }
+}
+
 enum Scene {
     MainMenu { selected: usize },
     Settings { selected: usize },
+    Controls {
+        row: usize,     // an action, then Reset and Back
+        slot: usize,    // which of the action's keys
+        capturing: bool, // waiting for the new key
+    },
     Playing,
-    GameOver { best: f32, score: f32 },
+    Replay,
+    Leaderboard { mode: usize, hz: u32 },
+    GameOver {
+        mode: String, // id
+        entry: Entry,
+        rank: Option<usize>,
+        naming: bool, // typing initials for a new high score
+        ranked: bool, // false if the rules changed mid-run
+    },
 }
 
-struct World {
-    player: Player,
-    ghosts: Vec<Ghost>,
-    orbs: Vec<Orb>,
-
-    // History buffer for ghosts
-    history: VecDeque<InputFrame>,
-    history_max: usize,
-
-    // Timers and progression
-    time_alive: f32,
-    score: f32,
-    combo: f32,
-    last_collect_time: f32,
-
-    orb_spawn_timer: f32,
-
-    // Difficulty dial
-    difficulty: f32,
-
-    // Camera shake
-    shake_t: f32,
-    shake_amt: f32,
-
-    // Audio
-    audio: AudioSet,
-
-    // Meta
-    mode: GameMode,
-    config: ModeConfig,
-    settings: Settings,
+/// Where the pause overlay is, while a run is paused.
+enum Pause {
+    Menu { selected: usize },
+    Settings { selected: usize },
+    Controls { row: usize, slot: usize, capturing: bool },
 }
 
-impl World {
-    fn difficulty_spawn_interval(&self) -> f32 {
-        let mut s = ORB_SPAWN_BASE * (1.0 / (1.0 + 0.25 * self.difficulty)) / self.config.spawn_rate_mul;
-        s = s.max(ORB_SPAWN_MIN);
-        s
-    }
-
-    fn ghost_speed(&self) -> f32 {
-        // 1.0 means 60 samples/sec. Scale gently
-        (1.0 + 0.3 * self.difficulty) * self.config.ghost_speed_mul
-    }
-
-    fn ghost_ttl(&self) -> f32 {
-        // Longer lasting ghosts as difficulty increases, but cap it
-        (8.0 + self.difficulty * 2.0).min(18.0)
-    }
-
-    fn spawn_ghost(&mut self, recent_secs: f32) {
-        let frames_recent = (recent_secs / FIXED_DT) as usize;
-        if self.history.len() < frames_recent.saturating_add(10) {
-            return; // not enough data yet
-        }
-        let start = self.history.len() - frames_recent;
-        let samples: Vec<Vec2> = self.history.iter().skip(start).map(|f| f.pos).collect();
-        if samples.len() < 12 {
-            return;
-        }
-        let ghost = Ghost {
-            samples,
-            progress: 0.0,
-            speed: self.ghost_speed(),
-            radius: GHOST_RADIUS,
-            color: Color::new(1.0, 0.35, 0.35, 0.9),
-            ttl: self.ghost_ttl(),
-        };
-        self.ghosts.push(ghost);
-        if self.settings.audio_enabled {
-            play_sound(
-                &self.audio.ghost_spawn,
-                PlaySoundParams {
-                    looped: false,
-                    volume: 0.55 * self.settings.master_volume,
-                },
-            );
-        }
-    }
-
-    fn spawn_orb(&mut self, w: f32, h: f32) {
-        let o = Orb {
-            pos: rand_pos_away_from(self.player.pos, ORB_SAFE_RADIUS, w, h),
-            radius: ORB_RADIUS,
-            alive: true,
-        };
-        self.orbs.push(o);
-    }
-
-    fn add_shake(&mut self, power: f32, time: f32) {
-        if self.settings.shake_enabled {
-            self.shake_amt = self.shake_amt.max(power);
-            self.shake_t = self.shake_t.max(time);
-        }
-    }
-
-    fn camera_offset(&self) -> Vec2 {
-        if self.shake_t <= 0.0 {
-            return vec2(0.0, 0.0);
-        }
-        use macroquad::rand::gen_range;
-        vec2(
-            gen_range(-1.0, 1.0) * self.shake_amt,
-            gen_range(-1.0, 1.0) * self.shake_amt,
-        )
-    }
+/// How a run left the game loop.
+enum RunEnd {
+    Died,
+    Restart,
+    Quit,
 }
 
 // -------------------------------
@@ -431,6 +207,18 @@ async fn main() {
     let sfx_ghost = load_sound_from_bytes(&tone_wav(420.0, 0.18, 0.35, 0.004, 0.03))
         .await
         .unwrap();
+    let sfx_pop = load_sound_from_bytes(&tone_wav(660.0, 0.1, 0.4, 0.002, 0.05))
+        .await
+        .unwrap();
+    let sfx_close = load_sound_from_bytes(&tone_wav(1320.0, 0.08, 0.3, 0.002, 0.04))
+        .await
+        .unwrap();
+    let sfx_power = load_sound_from_bytes(&tone_wav(1100.0, 0.22, 0.35, 0.004, 0.08))
+        .await
+        .unwrap();
+    let sfx_decoy = load_sound_from_bytes(&tone_wav(200.0, 0.25, 0.45, 0.002, 0.05))
+        .await
+        .unwrap();
     let sfx_death = load_sound_from_bytes(&tone_wav(120.0, 0.45, 0.5, 0.0, 0.06))
         .await
         .unwrap();
@@ -441,86 +229,390 @@ async fn main() {
     let audio = AudioSet {
         collect: sfx_collect,
         ghost_spawn: sfx_ghost,
+        pop: sfx_pop,
+        close: sfx_close,
+        power: sfx_power,
+        decoy: sfx_decoy,
         death: sfx_death,
         drone: sfx_drone,
     };
 
-    let mut settings = Settings::default();
-    let mut mode = GameMode::Classic;
-    let mut best_scores = [0.0f32; 3];
+    let save::SaveData { mut settings, mut bindings, mut leaderboard, mut initials } = save::load();
+    if settings.fullscreen {
+        set_fullscreen(true);
+    }
+    let modes = load_modes();
+    let mut mode = 0usize; // index into `modes`
+    let mut seed_choice: Option<u64> = None; // None: fresh random seed per run
+    let mut last_seed: Option<u64> = None;
+    let mut last_replay: Option<Replay> = replays::load_latest();
+    let mut tuning_watch = TuningWatcher::new();
+    let mut notice: Option<Notice> = None;
+    let mut pads = Gamepads::new();
     let mut scene = Scene::MainMenu { selected: 0 };
 
     loop {
         clear_background(BLACK);
+        let nav = MenuInput::poll(&pads, &bindings);
 
         match scene {
             Scene::MainMenu { ref mut selected } => {
-                draw_main_menu(*selected, mode, &settings, &best_scores);
-                if let Some(action) = update_main_menu(selected, &mut mode) {
+                draw_main_menu(
+                    *selected,
+                    &modes[mode],
+                    seed_choice,
+                    last_replay.is_some(),
+                    &settings,
+                    &bindings,
+                    &leaderboard,
+                );
+                if let Some(action) = update_main_menu(
+                    nav,
+                    selected,
+                    &mut mode,
+                    modes.len(),
+                    &mut seed_choice,
+                    last_seed,
+                ) {
                     match action {
                         MainMenuAction::Start => scene = Scene::Playing,
+                        MainMenuAction::WatchReplay => {
+                            if last_replay.is_some() {
+                                scene = Scene::Replay;
+                            }
+                        }
+                        MainMenuAction::Leaderboard => {
+                            scene = Scene::Leaderboard { mode, hz: settings.tick_rate }
+                        }
                         MainMenuAction::Settings => scene = Scene::Settings { selected: 0 },
                         MainMenuAction::Quit => std::process::exit(0),
                     }
                 }
-                if is_key_pressed(KeyCode::F11) {
+                if bindings.pressed(Action::Fullscreen) {
                     settings.fullscreen = !settings.fullscreen;
                     set_fullscreen(settings.fullscreen);
                 }
             }
             Scene::Settings { ref mut selected } => {
-                draw_settings_menu(*selected, &settings);
-                if update_settings_menu(selected, &mut settings) {
-                    scene = Scene::MainMenu { selected: 0 };
+                draw_settings_menu(*selected, &settings, &bindings);
+                match update_settings_menu(nav, &bindings, selected, &mut settings) {
+                    Some(SettingsAction::Controls) => {
+                        scene = Scene::Controls { row: 0, slot: 0, capturing: false };
+                    }
+                    Some(SettingsAction::Back) => {
+                        persist(settings, &bindings, &leaderboard, &initials);
+                        scene = Scene::MainMenu { selected: 0 };
+                    }
+                    None => {}
+                }
+            }
+            Scene::Controls { ref mut row, ref mut slot, ref mut capturing } => {
+                draw_controls(*row, *slot, *capturing, &bindings, &settings);
+                if update_controls(nav, row, slot, capturing, &mut bindings, &mut notice) {
+                    persist(settings, &bindings, &leaderboard, &initials);
+                    scene = Scene::Settings { selected: ROW_CONTROLS };
                 }
             }
             Scene::Playing => {
-                let mut world = new_world(audio.clone(), settings, mode);
-                let mut acc = 0.0f32;
+                let seed = seed_choice.unwrap_or_else(rng::random_seed);
+                last_seed = Some(seed);
+                let mut world = new_world(&audio, &settings, &modes[mode], seed);
+                // Tuning overrides always layer on the mode's own values
+                let base = modes[mode].clone();
+                let mut tampered = false; // tuning changed or console used
+                if let Some(text) = tuning_watch.current() {
+                    match base.patched_tuning(&text) {
+                        Ok(t) => {
+                            // A file that changes nothing leaves the run ranked
+                            tampered = t != base.tuning;
+                            world.apply_tuning(t);
+                        }
+                        Err(e) => notice = Some(Notice::error(format!("Tuning error: {}", e))),
+                    }
+                }
+                let mut console = DevConsole::new();
+                let mut fx = Effects::default();
+                let mut source = LocalInput {
+                    bindings: bindings.clone(),
+                    scheme: settings.control_scheme,
+                    pad: Default::default(),
+                };
+                let mut replay = Replay::new(seed, world.config.clone(), world.arena, world.tick_rate);
+                let mut pacing = Pacing::new(world.dt);
+                let mut paused: Option<Pause> = None;
+                let mut killcam = KillCam::new();
+
+                let end = 'game: loop {
+                    if let Some(text) = tuning_watch.poll(get_time()) {
+                        match base.patched_tuning(&text) {
+                            Ok(t) => {
+                                world.apply_tuning(t);
+                                tampered = true;
+                                notice = Some(Notice::info("Tuning reloaded".to_string()));
+                            }
+                            Err(e) => notice = Some(Notice::error(format!("Tuning error: {}", e))),
+                        }
+                    }
+
+                    // Esc closes the console before it can pause
+                    let console_was_open = console.open;
+                    if paused.is_none() && console.update(&mut world) {
+                        tampered = true;
+                    }
+                    source.pad = pads.state();
+                    let nav = MenuInput::poll(&pads, &bindings);
+
+                    let dt = get_frame_time();
+                    let stalled =
+                        dt > STALL_GAP || screen_width() < 1.0 || screen_height() < 1.0;
+                    let wants_pause = !console_was_open
+                        && !console.open
+                        && (bindings.pressed(Action::Menu) || pads.start_pressed());
+                    let just_paused = paused.is_none() && (stalled || wants_pause);
+                    if just_paused {
+                        paused = Some(Pause::Menu { selected: 0 });
+                        stop_sound(&audio.drone);
+                    }
 
-                'game: loop {
-                    let dt = get_frame_time() as f32;
-                    acc += dt;
+                    // Paused: the accumulator is left untouched, so play picks
+                    // up on the exact tick it stopped
+                    if let Some(view) = &mut paused {
+                        draw_world(&world, &settings, &fx, pacing.alpha());
+                        let mut resume = false;
+                        match view {
+                            Pause::Menu { selected } => {
+                                draw_pause_menu(*selected, &bindings);
+                                // The key that paused shouldn't also pick an item
+                                let action = if just_paused {
+                                    None
+                                } else if pads.start_pressed() {
+                                    Some(PauseAction::Resume)
+                                } else {
+                                    update_pause_menu(nav, selected)
+                                };
+                                match action {
+                                    Some(PauseAction::Resume) => resume = true,
+                                    Some(PauseAction::Restart) => break 'game RunEnd::Restart,
+                                    Some(PauseAction::Settings) => {
+                                        *view = Pause::Settings { selected: 0 };
+                                    }
+                                    Some(PauseAction::Quit) => break 'game RunEnd::Quit,
+                                    None => {}
+                                }
+                            }
+                            Pause::Settings { selected } => {
+                                draw_settings_menu(*selected, &settings, &bindings);
+                                let action =
+                                    update_settings_menu(nav, &bindings, selected, &mut settings);
+                                match action {
+                                    Some(SettingsAction::Controls) => {
+                                        *view =
+                                            Pause::Controls { row: 0, slot: 0, capturing: false };
+                                    }
+                                    Some(SettingsAction::Back) => {
+                                        persist(settings, &bindings, &leaderboard, &initials);
+                                        source.scheme = settings.control_scheme;
+                                        *view = Pause::Menu { selected: 2 };
+                                    }
+                                    None => {}
+                                }
+                            }
+                            Pause::Controls { row, slot, capturing } => {
+                                draw_controls(*row, *slot, *capturing, &bindings, &settings);
+                                let done = update_controls(
+                                    nav,
+                                    row,
+                                    slot,
+                                    capturing,
+                                    &mut bindings,
+                                    &mut notice,
+                                );
+                                if done {
+                                    persist(settings, &bindings, &leaderboard, &initials);
+                                    source.bindings = bindings.clone();
+                                    *view = Pause::Settings { selected: ROW_CONTROLS };
+                                }
+                            }
+                        }
+                        if resume {
+                            paused = None;
+                            if settings.audio_enabled {
+                                play_sound(
+                                    &audio.drone,
+                                    PlaySoundParams {
+                                        looped: true,
+                                        volume: 0.15 * settings.master_volume,
+                                    },
+                                );
+                            }
+                        }
+                        if let Some(n) = &notice {
+                            n.draw();
+                        }
+                        if let Some(msg) = pads.next_frame().await {
+                            notice = Some(Notice::info(msg));
+                        }
+                        continue;
+                    }
 
-                    while acc >= FIXED_DT {
-                        if step(&mut world) {
-                            // game over
-                            break 'game;
+                    pacing.feed(dt, console.timescale);
+
+                    while pacing.tick() {
+                        // Typing into the console shouldn't steer the player
+                        let input = if console.open {
+                            Default::default()
+                        } else {
+                            source.next_input(&world).unwrap_or_default()
+                        };
+                        replay.inputs.push(input);
+                        let over = step(&mut world, input);
+                        killcam.record(&world, over);
+                        if over {
+                            break 'game RunEnd::Died;
                         }
-                        acc -= FIXED_DT;
                     }
 
-                    draw_world(&world);
-                    next_frame().await;
+                    play_events(&mut world, &mut fx, &audio, &settings);
+                    fx.update(dt);
+                    draw_world(&world, &settings, &fx, pacing.alpha());
+                    if settings.control_scheme == ControlScheme::Pointer {
+                        draw_pointer_target(&world);
+                    }
+                    console.draw(&world, &settings, &pacing);
+                    if let Some(n) = &notice {
+                        n.draw();
+                    }
+                    if let Some(msg) = pads.next_frame().await {
+                        notice = Some(Notice::info(msg));
+                    }
+                };
+
+                // Abandoned runs are dropped: no replay, no results
+                match end {
+                    RunEnd::Restart => continue,
+                    RunEnd::Quit => {
+                        scene = Scene::MainMenu { selected: 0 };
+                        continue;
+                    }
+                    RunEnd::Died => {}
                 }
 
-                if world.settings.audio_enabled {
+                if settings.audio_enabled {
                     play_sound(
-                        &world.audio.death,
-                        PlaySoundParams { looped: false, volume: 0.7 * world.settings.master_volume },
+                        &audio.death,
+                        PlaySoundParams { looped: false, volume: 0.7 * settings.master_volume },
                     );
-                    stop_sound(&world.audio.drone);
+                    stop_sound(&audio.drone);
                 }
 
-                let idx = world.mode.index();
-                best_scores[idx] = best_scores[idx].max(world.score);
-                scene = Scene::GameOver { best: best_scores[idx], score: world.score };
+                // Time-limit endings have no killer and skip the kill-cam
+                let killer = world.events.iter().find_map(|e| match e {
+                    Event::Died { killer } => Some(*killer),
+                    _ => None,
+                });
+                if let Some(killer) = killer {
+                    killcam.play(killer, &settings, &bindings, &mut pads).await;
+                }
+
+                // A replay can't reproduce mid-run tuning or console commands,
+                // so such runs are neither saved nor ranked
+                let ranked = !tampered;
+                if ranked {
+                    if let Err(e) = replays::save(&replay) {
+                        eprintln!("failed to save replay: {}", e);
+                    }
+                    last_replay = Some(replay);
+                }
+
+                let mut entry = Entry::from_world(&world);
+                let naming = ranked && leaderboard.qualifies(&world.config.id, world.tick_rate, entry.score);
+                if naming {
+                    entry.initials = initials.clone();
+                    clear_input_queue(); // drop anything typed during the run
+                } else {
+                    persist(settings, &bindings, &leaderboard, &initials);
+                }
+                scene = Scene::GameOver {
+                    mode: world.config.id.clone(),
+                    entry,
+                    rank: None,
+                    naming,
+                    ranked,
+                };
             }
-            Scene::GameOver { best, score } => {
-                draw_game_over(score, best);
-                if is_key_pressed(KeyCode::Enter) {
+            Scene::Replay => {
+                if let Some(replay) = &last_replay {
+                    watch_replay(replay, &audio, &settings, &bindings, &mut pads).await;
+                }
+                scene = Scene::MainMenu { selected: 3 };
+            }
+            Scene::Leaderboard { ref mut mode, ref mut hz } => {
+                draw_leaderboard(&modes[*mode], *hz, &leaderboard, &settings, &bindings);
+                if nav.left {
+                    *mode = (*mode + modes.len() - 1) % modes.len();
+                }
+                if nav.right {
+                    *mode = (*mode + 1) % modes.len();
+                }
+                if nav.up {
+                    *hz = cycle_tick_rate(*hz, TICK_RATES.len() - 1);
+                }
+                if nav.down {
+                    *hz = cycle_tick_rate(*hz, 1);
+                }
+                if nav.back || nav.confirm {
+                    scene = Scene::MainMenu { selected: 4 };
+                }
+            }
+            Scene::GameOver { ref mode, ref mut entry, ref mut rank, ref mut naming, ranked } => {
+                draw_game_over(entry, leaderboard.best(mode, entry.tick_rate), *rank, *naming, ranked, &bindings);
+                if *naming {
+                    while let Some(c) = get_char_pressed() {
+                        if c.is_ascii_alphanumeric() && entry.initials.len() < INITIALS_LEN {
+                            entry.initials.push(c.to_ascii_uppercase());
+                        }
+                    }
+                    if is_key_pressed(KeyCode::Backspace) {
+                        entry.initials.pop();
+                    }
+                    // Pad only: on a keyboard these keys are for typing
+                    edit_initials(&mut entry.initials, pads.pressed());
+                    if nav.confirm && !entry.initials.is_empty() {
+                        initials = entry.initials.clone();
+                        *rank = leaderboard.insert(mode, entry.clone());
+                        *naming = false;
+                        persist(settings, &bindings, &leaderboard, &initials);
+                    }
+                } else if nav.confirm {
                     scene = Scene::Playing;
-                } else if is_key_pressed(KeyCode::Escape) {
+                } else if nav.back {
                     scene = Scene::MainMenu { selected: 0 };
                 }
-                if is_key_pressed(KeyCode::F11) {
+                if bindings.pressed(Action::Fullscreen) {
                     settings.fullscreen = !settings.fullscreen;
                     set_fullscreen(settings.fullscreen);
                 }
             }
         }
 
-        next_frame().await;
+        if let Some(n) = &notice {
+            n.draw();
+        }
+        if let Some(msg) = pads.next_frame().await {
+            notice = Some(Notice::info(msg));
+        }
+    }
+}
+
+fn persist(settings: Settings, bindings: &Bindings, leaderboard: &Leaderboard, initials: &str) {
+    let data = save::SaveData {
+        settings,
+        bindings: bindings.clone(),
+        leaderboard: leaderboard.clone(),
+        initials: initials.to_string(),
+    };
+    if let Err(e) = save::store(&data) {
+        eprintln!("failed to write save file: {}", e);
     }
 }
 
@@ -538,42 +630,34 @@ fn window_conf() -> Conf {
 // -------------------------------
 // World creation
 // -------------------------------
-fn new_world(audio: AudioSet, settings: Settings, mode: GameMode) -> World {
-    let history_max = (INPUT_HISTORY_SECONDS / FIXED_DT) as usize;
-    let config = mode_config(mode);
-
-    let mut w = World {
-        player: Player::new(vec2(screen_width() * 0.5, screen_height() * 0.5)),
-        ghosts: Vec::new(),
-        orbs: Vec::new(),
-
-        history: VecDeque::with_capacity(history_max + 1),
-        history_max,
-
-        time_alive: 0.0,
-        score: 0.0,
-        combo: 1.0,
-        last_collect_time: -999.0,
-
-        orb_spawn_timer: 0.0,
-
-        difficulty: 0.0,
+/// Built-in modes, then any `modes/*.toml` in the working directory and the
+/// user's config directory. Broken files are reported and skipped.
+fn load_modes() -> Vec<ModeConfig> {
+    let mut modes = builtin_modes();
+    let mut dirs = vec![std::path::PathBuf::from(MODES_DIR)];
+    dirs.extend(save::app_dir().map(|d| d.join(MODES_DIR)));
+    for dir in dirs {
+        let loaded = load_dir(&dir).into_iter().filter_map(|r| match r {
+            Ok(m) => Some(m),
+            Err((path, e)) => {
+                eprintln!("skipping mode file {}: {}", path, e);
+                None
+            }
+        });
+        merge_modes(&mut modes, loaded);
+    }
+    modes
+}
 
-        shake_t: 0.0,
-        shake_amt: 0.0,
+fn new_world(audio: &AudioSet, settings: &Settings, mode: &ModeConfig, seed: u64) -> World {
+    let w = World::with_tick_rate(mode.clone(), seed, ARENA, settings.tick_rate);
 
-        audio,
-        mode,
-        config,
-        settings,
-    };
-
-    if w.settings.audio_enabled {
+    if settings.audio_enabled {
         play_sound(
-            &w.audio.drone,
+            &audio.drone,
             PlaySoundParams {
                 looped: true,
-                volume: 0.15 * w.settings.master_volume,
+                volume: 0.15 * settings.master_volume,
             },
         );
     }
@@ -582,163 +666,160 @@ fn new_world(audio: AudioSet, settings: Settings, mode: GameMode) -> World {
 }
 
 // -------------------------------
-// One fixed-timestep step
-// Returns true on game over
+// Feedback for simulation events
 // -------------------------------
-fn step(w: &mut World) -> bool {
-    let sw = screen_width();
-    let sh = screen_height();
-    w.time_alive += FIXED_DT;
-    w.difficulty = w.config.difficulty_rate * w.time_alive + 0.002 * w.score; // mode ramp
-
-    if let Some(limit) = w.config.time_limit {
-        if w.time_alive >= limit {
-            return true;
+fn play_events(w: &mut World, fx: &mut Effects, audio: &AudioSet, settings: &Settings) {
+    for ev in w.events.drain(..) {
+        fx.on_event(&ev);
+        if !settings.audio_enabled {
+            continue;
         }
-    }
-
-    // Spawn orbs over time
-    w.orb_spawn_timer -= FIXED_DT;
-    if w.orb_spawn_timer <= 0.0 {
-        w.spawn_orb(sw, sh);
-        w.orb_spawn_timer = w.difficulty_spawn_interval();
-    }
-
-    // Read input
-    let mut dir = vec2(0.0, 0.0);
-    if is_key_down(KeyCode::A) || is_key_down(KeyCode::Left) {
-        dir.x -= 1.0;
-    }
-    if is_key_down(KeyCode::D) || is_key_down(KeyCode::Right) {
-        dir.x += 1.0;
-    }
-    if is_key_down(KeyCode::W) || is_key_down(KeyCode::Up) {
-        dir.y -= 1.0;
-    }
-    if is_key_down(KeyCode::S) || is_key_down(KeyCode::Down) {
-        dir.y += 1.0;
-    }
-    if dir.length_squared() > 1.0 {
-        dir = dir.normalize();
-    }
-
-    // Phase ability
-    let want_phase =
-        is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift) || is_key_down(KeyCode::Space);
-    if want_phase && w.player.phase_energy > 0.0 {
-        w.player.phase_active = true;
-        w.player.phase_energy -= PHASE_DRAIN * FIXED_DT;
-        if w.player.phase_energy <= 0.0 {
-            w.player.phase_energy = 0.0;
-            w.player.phase_active = false;
+        match ev {
+            Event::OrbCollected { .. } => play_sound(
+                &audio.collect,
+                PlaySoundParams {
+                    looped: false,
+                    volume: 0.55 * settings.master_volume,
+                },
+            ),
+            Event::PowerUp { kind, .. } => play_sound(
+                if kind == OrbKind::Decoy { &audio.decoy } else { &audio.power },
+                PlaySoundParams {
+                    looped: false,
+                    volume: 0.5 * settings.master_volume,
+                },
+            ),
+            Event::ChainCompleted { .. } => play_sound(
+                &audio.power,
+                PlaySoundParams {
+                    looped: false,
+                    volume: 0.6 * settings.master_volume,
+                },
+            ),
+            Event::ChainBroken { .. } => play_sound(
+                &audio.pop,
+                PlaySoundParams {
+                    looped: false,
+                    volume: 0.4 * settings.master_volume,
+                },
+            ),
+            Event::GhostSpawned => play_sound(
+                &audio.ghost_spawn,
+                PlaySoundParams {
+                    looped: false,
+                    volume: 0.55 * settings.master_volume,
+                },
+            ),
+            Event::GhostsMerged { .. } | Event::GhostsAnnihilated { .. } => play_sound(
+                &audio.pop,
+                PlaySoundParams {
+                    looped: false,
+                    volume: 0.5 * settings.master_volume,
+                },
+            ),
+            Event::NearMiss { .. } | Event::PhasedThrough { .. } => play_sound(
+                &audio.close,
+                PlaySoundParams {
+                    looped: false,
+                    volume: 0.45 * settings.master_volume,
+                },
+            ),
+            // The death sting is played by the scene once the run ends
+            Event::Died { .. } => {}
         }
-    } else {
-        w.player.phase_active = false;
-        w.player.phase_energy = (w.player.phase_energy + PHASE_REGEN * FIXED_DT).min(PHASE_MAX);
-    }
-
-    // Physics
-    w.player.vel += dir * ACCEL * FIXED_DT;
-    if w.player.vel.length() > MAX_SPEED {
-        w.player.vel = w.player.vel.normalize() * MAX_SPEED;
-    }
-    // Friction
-    w.player.vel *= (1.0 - FRICTION * FIXED_DT).max(0.0);
-    w.player.pos += w.player.vel * FIXED_DT;
-
-    // Bounds clamp
-    let (clamped, _n) = clamp_rect(w.player.pos, w.player.radius, sw, sh);
-    w.player.pos = clamped;
-
-    // Push to history
-    w.history.push_back(InputFrame { pos: w.player.pos });
-    while w.history.len() > w.history_max {
-        w.history.pop_front();
-    }
-
-    // Update ghosts
-    for g in &mut w.ghosts {
-        g.ttl -= FIXED_DT;
-        g.progress += g.speed * FIXED_DT * 60.0; // samples/sec scaled vs 60fps recording
     }
-    w.ghosts.retain(|g| g.ttl > 0.0);
+}
 
-    // Collisions with ghosts
-    if !w.player.phase_active {
-        for g in &w.ghosts {
-            if circle_overlap(w.player.pos, w.player.radius, g.current_pos(), g.radius) {
-                // game over
-                w.add_shake(8.0, 0.25);
-                return true;
+// -------------------------------
+// Replay playback
+// -------------------------------
+async fn watch_replay(
+    replay: &Replay,
+    audio: &AudioSet,
+    settings: &Settings,
+    bindings: &Bindings,
+    pads: &mut Gamepads,
+) {
+    let total = replay.inputs.len() as u64;
+    let mut speed_idx = 2usize; // 1.0x
+    let mut paused = false;
+
+    'restart: loop {
+        let mut world = replay.world();
+        let mut source = replay.input();
+        let mut fx = Effects::default();
+        let mut pacing = Pacing::new(world.dt);
+        let mut finished = false;
+
+        loop {
+            let nav = MenuInput::poll(pads, bindings);
+            if nav.back {
+                return;
+            }
+            if is_key_pressed(KeyCode::R) {
+                continue 'restart;
+            }
+            if is_key_pressed(KeyCode::Space) || nav.confirm {
+                paused = !paused;
+            }
+            if nav.left {
+                speed_idx = speed_idx.saturating_sub(1);
+            }
+            if nav.right {
+                speed_idx = (speed_idx + 1).min(REPLAY_SPEEDS.len() - 1);
             }
-        }
-    }
-
-    // Collide with orbs
-    let mut collected_count = 0u32;
-    for o in &mut w.orbs {
-        if o.alive && circle_overlap(w.player.pos, w.player.radius, o.pos, o.radius) {
-            o.alive = false;
-            collected_count += 1;
 
-            // Score and combo
-            let since = (w.time_alive - w.last_collect_time).max(0.0);
-            if since < 1.6 {
-                w.combo += 0.25;
+            // How many ticks to advance this frame
+            let mut ticks = 0;
+            if paused {
+                pacing.reset();
+                if is_key_pressed(KeyCode::Period) {
+                    ticks = 1;
+                }
             } else {
-                w.combo = (w.combo - COMBO_DECAY_PER_SEC * since).max(1.0);
-                w.combo += 0.15;
+                pacing.feed(get_frame_time(), REPLAY_SPEEDS[speed_idx]);
+                while pacing.tick() {
+                    ticks += 1;
+                }
             }
-            w.last_collect_time = w.time_alive;
-
-            let gain = 45.0 * w.combo;
-            w.score += gain;
-        }
-    }
-    if collected_count > 0 {
-        // Spawn ghosts: replay last 2.6..5.0s depending on difficulty
-        let secs = lerp(2.6, 5.0, (w.difficulty / 12.0).min(1.0));
-        for _ in 0..collected_count {
-            w.spawn_ghost(secs);
-        }
-        // SFX + shake
-        if w.settings.audio_enabled {
-            play_sound(
-                &w.audio.collect,
-                PlaySoundParams {
-                    looped: false,
-                    volume: 0.55 * w.settings.master_volume,
-                },
-            );
-        }
-        w.add_shake(3.0, 0.12);
-    }
-    w.orbs.retain(|o| o.alive);
 
-    // Passive score over time with combo influence that decays slowly
-    let decay = COMBO_DECAY_PER_SEC * FIXED_DT;
-    w.combo = (w.combo - decay).max(1.0);
-    w.score += (2.0 + w.difficulty * 0.4) * FIXED_DT * w.combo;
+            for _ in 0..ticks {
+                if finished {
+                    break;
+                }
+                finished = match source.next_input(&world) {
+                    Some(input) => step(&mut world, input),
+                    None => true,
+                };
+            }
 
-    // Camera shake timer
-    if w.shake_t > 0.0 {
-        w.shake_t -= FIXED_DT;
-        if w.shake_t <= 0.0 {
-            w.shake_t = 0.0;
-            w.shake_amt = 0.0;
+            play_events(&mut world, &mut fx, audio, settings);
+            fx.update(get_frame_time());
+            let alpha = if paused { 1.0 } else { pacing.alpha() };
+            draw_world(&world, settings, &fx, alpha);
+            draw_replay_hud(world.tick, total, world.dt, REPLAY_SPEEDS[speed_idx], paused, finished);
+            pads.next_frame().await;
         }
     }
+}
 
-    false
+fn camera_offset(w: &World, settings: &Settings) -> Vec2 {
+    if !settings.shake_enabled {
+        return vec2(0.0, 0.0);
+    }
+    w.camera_offset()
 }
 
 // -------------------------------
 // Rendering
 // -------------------------------
-fn draw_world(w: &World) {
-    let sw = screen_width();
-    let sh = screen_height();
-    let cam_off = w.camera_offset();
+/// The arena and effects in arena coordinates, then the HUD over the window.
+/// `alpha` is how far the display is past the last tick, 0..1.
+fn draw_world(w: &World, settings: &Settings, fx: &Effects, alpha: f32) {
+    let (sw, sh) = (w.arena.x, w.arena.y);
+    let cam_off = camera_offset(w, settings);
+    clear_background(BLACK);
+    set_camera(&view::arena_camera(w.arena));
 
     // Arena background
     let bg = Color::new(0.06, 0.07, 0.10, 1.0);
@@ -767,38 +848,56 @@ fn draw_world(w: &World) {
         );
     }
 
-    // Orbs
+    // Obstacles
+    for o in &w.obstacles {
+        let p = o.render_pos(alpha) + cam_off;
+        draw_obstacle(o, p, Color::new(0.16, 0.18, 0.26, 1.0), true);
+        draw_obstacle(o, p, Color::new(0.4, 0.45, 0.6, 0.8), false);
+    }
+
+    // Orbs, fading out over their last moments
     for o in &w.orbs {
-        draw_circle(o.pos.x + cam_off.x, o.pos.y + cam_off.y, o.radius, YELLOW);
-        draw_circle_lines(
-            o.pos.x + cam_off.x,
-            o.pos.y + cam_off.y,
-            o.radius + 3.0,
-            2.0,
-            Color::new(0.9, 0.8, 0.2, 0.5),
-        );
+        let fade = o.ttl.map_or(1.0, |t| (t / ORB_FADE).clamp(0.0, 1.0));
+        let c = orb_color(o.kind);
+        let p = interpolate(o.prev_pos, o.pos, alpha) + cam_off;
+        let (x, y) = (p.x, p.y);
+        draw_circle(x, y, o.radius, Color { a: fade, ..c });
+        draw_circle_lines(x, y, o.radius + 3.0, 2.0, Color { a: 0.5 * fade, ..c });
+        if let Some(n) = o.chain {
+            if w.chain.is_some_and(|ch| ch.next == n) {
+                let pulse = 6.0 + 3.0 * (w.time_alive * 8.0).sin().abs();
+                draw_circle_lines(x, y, o.radius + pulse, 2.0, Color { a: fade, ..WHITE });
+            }
+            let label = n.to_string();
+            let d = measure_text(&label, None, 18, 1.0);
+            draw_text(&label, x - d.width * 0.5, y + 5.0, 18.0, Color { a: fade, ..BLACK });
+        }
     }
 
     // Ghosts, draw path hints sparsely and current position
     for g in &w.ghosts {
-        let mut alpha = (g.ttl / (g.ttl + 1.0)).clamp(0.25, 0.9);
+        let mut fade = (g.ttl / (g.ttl + 1.0)).clamp(0.25, 0.9);
+        if !g.armed {
+            fade *= 0.5; // harmless until it has cleared the player
+        }
         if w.config.ghost_flicker {
-            let flick = (w.time_alive * 7.0 + g.progress * 0.05).sin().abs();
-            alpha *= 0.4 + 0.6 * flick;
+            let flick = (w.time_alive * 7.0 + g.progress * 3.0).sin().abs();
+            fade *= 0.4 + 0.6 * flick;
         }
-        let pos = g.current_pos();
+        let pos = g.render_pos(alpha);
         if w.config.ghost_invisible_far {
-            let dist = pos.distance(w.player.pos);
-            if dist > 220.0 { alpha *= 0.25; }
+            let dist = g.current_pos().distance(w.player.pos);
+            if dist > 220.0 { fade *= 0.25; }
         }
-        let c = Color::new(0.95, 0.25, 0.25, alpha);
+        let c = Color { a: fade, ..ghost_color(g.kind) };
         draw_circle(pos.x + cam_off.x, pos.y + cam_off.y, g.radius, c);
 
         // Sparse path dots
         let step = (g.samples.len() / 24).max(4);
-        for (i, s) in g.samples.iter().enumerate().step_by(step) {
-            let _ = i; // silence unused warning when step is large
-            draw_circle(s.x + cam_off.x, s.y + cam_off.y, 2.0, Color::new(0.8, 0.2, 0.2, 0.18));
+        let dot = Color { a: 0.18, ..ghost_color(g.kind) };
+        for s in g.samples.iter().step_by(step) {
+            let s = s.pos + g.offset;
+            draw_circle(s.x + cam_off.x, s.y + cam_off.y, 2.0, dot);
         }
     }
 
@@ -808,25 +907,82 @@ fn draw_world(w: &World) {
     } else {
         Color::new(0.35, 0.75, 1.0, 1.0)
     };
-    draw_circle(
-        w.player.pos.x + cam_off.x,
-        w.player.pos.y + cam_off.y,
-        w.player.radius,
-        pc,
-    );
-    draw_circle_lines(
-        w.player.pos.x + cam_off.x,
-        w.player.pos.y + cam_off.y,
-        w.player.radius + 4.0,
-        2.0,
-        Color::new(0.2, 0.45, 0.9, 0.65),
-    );
+    let pp = interpolate(w.player.prev_pos, w.player.pos, alpha) + cam_off;
+    draw_circle(pp.x, pp.y, w.player.radius, pc);
+    draw_circle_lines(pp.x, pp.y, w.player.radius + 4.0, 2.0, Color::new(0.2, 0.45, 0.9, 0.65));
+
+    fx.draw(cam_off);
+    set_default_camera();
+    view::draw_letterbox(w.arena);
 
     // UI
     draw_ui(w);
 
     // Horror vignette overlay
-    draw_vignette(sw, sh, w.settings.vignette, w.difficulty, w.config.ghost_flicker);
+    draw_vignette(
+        screen_width(),
+        screen_height(),
+        settings.vignette,
+        w.difficulty,
+        w.config.ghost_flicker,
+    );
+}
+
+/// Plain echoes keep the classic red; other kinds shift hue so players can
+/// tell what they're up against.
+fn ghost_color(kind: GhostKind) -> Color {
+    match kind {
+        GhostKind::Echo => GHOST_COLOR,
+        GhostKind::Reverse => Color::new(0.95, 0.45, 0.2, 0.9),
+        GhostKind::Mirror => Color::new(0.9, 0.3, 0.75, 0.9),
+        GhostKind::PingPong => Color::new(0.95, 0.7, 0.25, 0.9),
+        GhostKind::Shadow => Color::new(0.55, 0.35, 0.95, 0.9),
+        GhostKind::Homing => Color::new(1.0, 0.15, 0.45, 0.9),
+    }
+}
+
+/// Filled, or as a 2px outline of its collision shape, placed at `p`.
+fn draw_obstacle(o: &Obstacle, p: Vec2, c: Color, fill: bool) {
+    match o.shape {
+        Shape::Pillar { radius } if fill => draw_circle(p.x, p.y, radius, c),
+        Shape::Pillar { radius } => draw_circle_lines(p.x, p.y, radius, 2.0, c),
+        Shape::Block { half } => {
+            let (x, y, w, h) = (p.x - half.x, p.y - half.y, half.x * 2.0, half.y * 2.0);
+            if fill {
+                draw_rectangle(x, y, w, h, c);
+            } else {
+                draw_rectangle_lines(x, y, w, h, 2.0, c);
+            }
+        }
+        Shape::Wall { to, thickness } => {
+            // A capsule around the segment p..q
+            let (q, r) = (p + to, thickness * 0.5);
+            if fill {
+                draw_line(p.x, p.y, q.x, q.y, thickness, c);
+                draw_circle(p.x, p.y, r, c);
+                draw_circle(q.x, q.y, r, c);
+            } else {
+                let side = to.perp().normalize_or_zero() * r;
+                for s in [side, -side] {
+                    draw_line(p.x + s.x, p.y + s.y, q.x + s.x, q.y + s.y, 2.0, c);
+                }
+                draw_circle_lines(p.x, p.y, r, 2.0, c);
+                draw_circle_lines(q.x, q.y, r, 2.0, c);
+            }
+        }
+    }
+}
+
+/// Score orbs stay yellow; decoys are red like the ghosts they spawn.
+fn orb_color(kind: OrbKind) -> Color {
+    match kind {
+        OrbKind::Score => YELLOW,
+        OrbKind::PhaseRefill => Color::new(0.25, 0.9, 0.95, 1.0),
+        OrbKind::SlowTime => Color::new(0.45, 0.55, 1.0, 1.0),
+        OrbKind::Purge => WHITE,
+        OrbKind::Multiplier => Color::new(0.35, 1.0, 0.4, 1.0),
+        OrbKind::Decoy => Color::new(0.95, 0.2, 0.2, 1.0),
+    }
 }
 
 fn draw_ui(w: &World) {
@@ -843,7 +999,7 @@ fn draw_ui(w: &World) {
     let x = 16.0;
     let y = 40.0;
     draw_rectangle_lines(x - 2.0, y - 2.0, bar_w + 4.0, bar_h + 4.0, 2.0, GRAY);
-    let t = (w.player.phase_energy / PHASE_MAX).clamp(0.0, 1.0);
+    let t = (w.player.phase_energy / w.config.tuning.phase_max).clamp(0.0, 1.0);
     draw_rectangle(x, y, bar_w * t, bar_h, Color::new(0.25, 0.9, 0.95, 0.9));
 
     // Difficulty indicator
@@ -858,48 +1014,41 @@ fn draw_ui(w: &World) {
     );
 
     // Mode label
-    let ml = format!("Mode: {}", w.mode.name());
+    let ml = format!("Mode: {}", w.config.name);
     draw_text(&ml, 16.0, 64.0, 22.0, GRAY);
-}
 
-fn draw_title_screen(best: f32) {
-    let sw = screen_width();
-    let sh = screen_height();
-    clear_background(BLACK);
-    // Title centered
-    let title = "Feedback Rush";
-    let td = measure_text(title, None, 64, 1.0);
-    draw_text(title, (sw - td.width) * 0.5, 120.0, 64.0, WHITE);
-    let subt = "Outmaneuver your own echoes.";
-    let sd = measure_text(subt, None, 28, 1.0);
-    draw_text(subt, (sw - sd.width) * 0.5, 160.0, 28.0, GRAY);
-
-    let controls = [
-        "WASD / Arrows - Move",
-        "Shift or Space - Phase (invulnerable, drains meter)",
-        "Collect orbs to score and spawn 'ghost' echoes",
-        "Avoid colliding with ghosts unless phasing",
-        "Your ghosts loop your past path at increasing speed",
-    ];
-    let mut y = 220.0;
-    for c in controls {
-        let cd = measure_text(c, None, 24, 1.0);
-        draw_text(c, (sw - cd.width) * 0.5, y, 24.0, LIGHTGRAY);
-        y += 28.0;
+    // Running power-ups
+    let mut y = 88.0;
+    if let Some(ch) = w.chain {
+        // Chain orbs share one timer
+        let left = w
+            .orbs
+            .iter()
+            .filter(|o| o.chain.is_some())
+            .filter_map(|o| o.ttl)
+            .fold(0.0, f32::max);
+        draw_text(&format!("CHAIN {}/{} {:.1}s", ch.next, ch.len, left), 16.0, y, 22.0, YELLOW);
+        y += 22.0;
     }
-
-    let prompt = "Press Enter to start";
-    let pd = measure_text(prompt, None, 28, 1.0);
-    draw_text(prompt, (sw - pd.width) * 0.5, sh - 64.0, 28.0, Color::new(0.8, 0.9, 1.0, 1.0));
-
-    if best > 0.0 {
-        let btxt = format!("Best Score: {}", best as i32);
-        let bd = measure_text(&btxt, None, 28, 1.0);
-        draw_text(&btxt, sw - bd.width - 24.0, sh - 36.0, 28.0, WHITE);
+    for (left, label, kind) in [
+        (w.slow_t, "SLOW", OrbKind::SlowTime),
+        (w.multiplier_t, "x2 SCORE", OrbKind::Multiplier),
+    ] {
+        if left > 0.0 {
+            draw_text(&format!("{} {:.1}s", label, left), 16.0, y, 22.0, orb_color(kind));
+            y += 22.0;
+        }
     }
 }
 
-fn draw_game_over(score: f32, best: f32) {
+fn draw_game_over(
+    entry: &Entry,
+    best: f32,
+    rank: Option<usize>,
+    naming: bool,
+    ranked: bool,
+    bindings: &Bindings,
+) {
     let sw = screen_width();
     let sh = screen_height();
     clear_background(Color::new(0.05, 0.05, 0.06, 1.0));
@@ -907,17 +1056,189 @@ fn draw_game_over(score: f32, best: f32) {
     let td = measure_text(t, None, 64, 1.0);
     draw_text(t, (sw - td.width) * 0.5, 120.0, 64.0, Color::new(1.0, 0.5, 0.5, 1.0));
 
-    let s1 = format!("Score: {}", score as i32);
+    let s1 = format!("Score: {}", entry.score as i32);
     let s1d = measure_text(&s1, None, 32, 1.0);
     draw_text(&s1, (sw - s1d.width) * 0.5, 170.0, 32.0, WHITE);
 
-    let s2 = format!("Best:  {}", best as i32);
+    let s2 = format!("Best:  {}", best.max(entry.score) as i32);
     let s2d = measure_text(&s2, None, 32, 1.0);
     draw_text(&s2, (sw - s2d.width) * 0.5, 206.0, 32.0, WHITE);
 
-    let p = "Enter - Restart / Esc - Menu";
-    let pd = measure_text(p, None, 28, 1.0);
-    draw_text(p, (sw - pd.width) * 0.5, sh - 64.0, 28.0, GRAY);
+    let s3 = format!(
+        "Time {:.1}s   Peak x{:.2}   Orbs {}   Ghosts {}   Seed {}",
+        entry.time_alive, entry.peak_combo, entry.orbs_collected, entry.ghosts_spawned, entry.seed
+    );
+    let s3d = measure_text(&s3, None, 22, 1.0);
+    draw_text(&s3, (sw - s3d.width) * 0.5, 240.0, 22.0, GRAY);
+    let s4 = format!("Near misses {}   Phased through {}", entry.near_misses, entry.phase_throughs);
+    let s4d = measure_text(&s4, None, 22, 1.0);
+    draw_text(&s4, (sw - s4d.width) * 0.5, 266.0, 22.0, GRAY);
+
+    if naming {
+        let blink = if (get_time() * 2.0) as i64 % 2 == 0 { "_" } else { " " };
+        let cursor = if entry.initials.len() < INITIALS_LEN { blink } else { "" };
+        let n1 = "New high score! Enter your initials:";
+        let n1d = measure_text(n1, None, 28, 1.0);
+        draw_text(n1, (sw - n1d.width) * 0.5, 300.0, 28.0, Color::new(0.8, 0.9, 1.0, 1.0));
+        let n2 = format!("{}{}", entry.initials, cursor);
+        let n2d = measure_text("WWW", None, 48, 1.0);
+        draw_text(&n2, (sw - n2d.width) * 0.5, 350.0, 48.0, WHITE);
+    } else if let Some(r) = rank {
+        let r1 = format!("Ranked #{} on the leaderboard", r + 1);
+        let r1d = measure_text(&r1, None, 28, 1.0);
+        draw_text(&r1, (sw - r1d.width) * 0.5, 300.0, 28.0, Color::new(0.8, 0.9, 1.0, 1.0));
+    } else if !ranked {
+        let r1 = "Rules changed mid-run (tuning or dev console) - not ranked or recorded";
+        let r1d = measure_text(r1, None, 24, 1.0);
+        draw_text(r1, (sw - r1d.width) * 0.5, 300.0, 24.0, GRAY);
+    }
+
+    let confirm = bindings.label(Action::Restart);
+    let p = if naming {
+        format!("Type initials - {} to confirm", confirm)
+    } else {
+        format!("{} - Restart / {} - Menu", confirm, bindings.label(Action::Menu))
+    };
+    let pd = measure_text(&p, None, 28, 1.0);
+    draw_text(&p, (sw - pd.width) * 0.5, sh - 64.0, 28.0, GRAY);
+}
+
+fn draw_leaderboard(
+    mode: &ModeConfig,
+    hz: u32,
+    board: &Leaderboard,
+    settings: &Settings,
+    bindings: &Bindings,
+) {
+    let sw = screen_width();
+    let sh = screen_height();
+    clear_background(BLACK);
+    let title = format!("< {} >", mode.name);
+    let td = measure_text(&title, None, 48, 1.0);
+    draw_text(&title, (sw - td.width) * 0.5, 80.0, 48.0, WHITE);
+    let rate = format!("{} Hz", hz);
+    let rd = measure_text(&rate, None, 22, 1.0);
+    draw_text(&rate, (sw - rd.width) * 0.5, 108.0, 22.0, GRAY);
+
+    // Fixed columns, centered as a block
+    let cols = [0.0, 50.0, 120.0, 230.0, 320.0, 400.0, 480.0, 560.0];
+    let x0 = (sw - 680.0) * 0.5;
+    let header = ["#", "Name", "Score", "Time", "Combo", "Orbs", "Ghosts", "Date"];
+    for (h, cx) in header.iter().zip(cols) {
+        draw_text(h, x0 + cx, 140.0, 22.0, GRAY);
+    }
+
+    let entries = board.top(&mode.id, hz);
+    if entries.is_empty() {
+        let e = "No runs yet";
+        let ed = measure_text(e, None, 26, 1.0);
+        draw_text(e, (sw - ed.width) * 0.5, 200.0, 26.0, LIGHTGRAY);
+    }
+    let mut y = 172.0;
+    for (i, e) in entries.iter().enumerate() {
+        let row = [
+            format!("{}", i + 1),
+            e.initials.clone(),
+            format!("{}", e.score as i32),
+            format!("{:.1}s", e.time_alive),
+            format!("x{:.2}", e.peak_combo),
+            format!("{}", e.orbs_collected),
+            format!("{}", e.ghosts_spawned),
+            e.date.clone(),
+        ];
+        let c = if i == 0 { Color::new(0.9, 0.9, 1.0, 1.0) } else { LIGHTGRAY };
+        for (txt, cx) in row.iter().zip(cols) {
+            draw_text(txt, x0 + cx, y, 22.0, c);
+        }
+        y += 28.0;
+    }
+
+    let hint = format!(
+        "{}/{}: Mode  |  {}/{}: Tick Rate  |  {}: Back",
+        bindings.label(Action::MoveLeft),
+        bindings.label(Action::MoveRight),
+        bindings.label(Action::MoveUp),
+        bindings.label(Action::MoveDown),
+        bindings.label(Action::Menu)
+    );
+    let hd = measure_text(&hint, None, 20, 1.0);
+    draw_text(&hint, (sw - hd.width) * 0.5, sh - 40.0, 20.0, DARKGRAY);
+
+    draw_vignette(sw, sh, settings.vignette, 0.0, false);
+}
+
+/// Where the player is being steered in pointer mode.
+fn draw_pointer_target(w: &World) {
+    let p = input::pointer(w.arena);
+    let c = Color::new(0.35, 0.75, 1.0, 0.35);
+    set_camera(&view::arena_camera(w.arena));
+    draw_line(w.player.pos.x, w.player.pos.y, p.target.x, p.target.y, 1.0, c);
+    draw_circle_lines(p.target.x, p.target.y, 6.0, 1.5, c);
+    set_default_camera();
+}
+
+/// A short-lived line of status text at the top of the screen.
+struct Notice {
+    text: String,
+    error: bool,
+    shown_at: f64,
+}
+
+impl Notice {
+    fn info(text: String) -> Self {
+        Self { text, error: false, shown_at: get_time() }
+    }
+
+    fn error(text: String) -> Self {
+        Self { text, error: true, shown_at: get_time() }
+    }
+
+    fn draw(&self) {
+        // Errors linger so there's time to read the parse message
+        let life = if self.error { 6.0 } else { 2.5 };
+        let age = (get_time() - self.shown_at) as f32;
+        if age >= life {
+            return;
+        }
+        let a = (life - age).min(0.5) * 2.0;
+        let c = if self.error {
+            Color::new(1.0, 0.45, 0.45, a)
+        } else {
+            Color::new(0.6, 1.0, 0.7, a)
+        };
+        let sw = screen_width();
+        let d = measure_text(&self.text, None, 22, 1.0);
+        let x = ((sw - d.width) * 0.5).max(8.0);
+        draw_rectangle(x - 8.0, 78.0, d.width + 16.0, 30.0, Color::new(0.0, 0.0, 0.0, 0.6 * a));
+        draw_text(&self.text, x, 99.0, 22.0, c);
+    }
+}
+
+fn draw_replay_hud(tick: u64, total: u64, dt: f32, speed: f32, paused: bool, finished: bool) {
+    let sw = screen_width();
+    let sh = screen_height();
+
+    let state = if finished {
+        "END".to_string()
+    } else if paused {
+        "PAUSED".to_string()
+    } else {
+        format!("{:.2}x", speed)
+    };
+    let s = format!("REPLAY  {}   {:.1}s / {:.1}s", state, tick as f32 * dt, total as f32 * dt);
+    let sd = measure_text(&s, None, 24, 1.0);
+    draw_text(&s, (sw - sd.width) * 0.5, sh - 56.0, 24.0, Color::new(0.8, 0.9, 1.0, 1.0));
+
+    // Progress bar
+    let bar_w = sw * 0.5;
+    let x = (sw - bar_w) * 0.5;
+    let t = if total > 0 { (tick as f32 / total as f32).min(1.0) } else { 1.0 };
+    draw_rectangle(x, sh - 44.0, bar_w, 4.0, Color::new(0.2, 0.2, 0.25, 1.0));
+    draw_rectangle(x, sh - 44.0, bar_w * t, 4.0, Color::new(0.8, 0.9, 1.0, 1.0));
+
+    let hint = "Space: Pause  |  .: Step  |  Left/Right: Speed  |  R: Restart  |  Esc: Menu";
+    let hd = measure_text(hint, None, 20, 1.0);
+    draw_text(hint, (sw - hd.width) * 0.5, sh - 16.0, 20.0, DARKGRAY);
 }
 
 fn draw_vignette(sw: f32, sh: f32, strength: f32, threat: f32, pulse: bool) {
@@ -942,7 +1263,15 @@ fn draw_vignette(sw: f32, sh: f32, strength: f32, threat: f32, pulse: bool) {
     }
 }
 
-fn draw_main_menu(selected: usize, mode: GameMode, settings: &Settings, bests: &[f32; 3]) {
+fn draw_main_menu(
+    selected: usize,
+    mode: &ModeConfig,
+    seed: Option<u64>,
+    has_replay: bool,
+    settings: &Settings,
+    bindings: &Bindings,
+    board: &Leaderboard,
+) {
     let sw = screen_width();
     let sh = screen_height();
     clear_background(BLACK);
@@ -954,68 +1283,181 @@ fn draw_main_menu(selected: usize, mode: GameMode, settings: &Settings, bests: &
     let sd = measure_text(subt, None, 24, 1.0);
     draw_text(subt, (sw - sd.width) * 0.5, 150.0, 24.0, GRAY);
 
+    let seed_label = match seed {
+        Some(s) => format!("Seed: {}", s),
+        None => "Seed: Random".to_string(),
+    };
     let items = [
         "Start Game",
-        &format!("Mode: {}", mode.name()),
+        &format!("Mode: {}", mode.name),
+        &seed_label,
+        if has_replay { "Watch Replay" } else { "Watch Replay (none yet)" },
+        "Leaderboard",
         "Settings",
         "Quit",
     ];
-    let mut y = 220.0;
+    let mut y = 210.0;
     for (i, txt) in items.iter().enumerate() {
         let c = if i == selected { Color::new(0.9, 0.9, 1.0, 1.0) } else { LIGHTGRAY };
         let size = if i == selected { 30.0 } else { 26.0 };
         let md = measure_text(txt, None, size as u16, 1.0);
         draw_text(txt, (sw - md.width) * 0.5, y, size, c);
-        y += 36.0;
+        y += 34.0;
     }
 
-    let best = bests[mode.index()] as i32;
-    let btxt = format!("Best {}: {}", mode.name(), best);
+    let best = board.best(&mode.id, settings.tick_rate) as i32;
+    let btxt = format!("Best {} at {} Hz: {}", mode.name, settings.tick_rate, best);
     let bd = measure_text(&btxt, None, 22, 1.0);
     draw_text(&btxt, (sw - bd.width) * 0.5, y + 16.0, 22.0, GRAY);
 
-    let hint = "Enter: Select  |  Arrows: Navigate  |  F11: Fullscreen";
-    let hd = measure_text(hint, None, 20, 1.0);
-    draw_text(hint, (sw - hd.width) * 0.5, sh - 40.0, 20.0, DARKGRAY);
+    let b = bindings;
+    let hint = if selected == 2 {
+        format!(
+            "Type digits to set a seed  |  Backspace: Erase  |  {}/{}: Random / Last run",
+            b.label(Action::MoveLeft),
+            b.label(Action::MoveRight)
+        )
+    } else {
+        format!(
+            "{}: Select  |  {}/{}: Navigate  |  {}: Fullscreen",
+            b.label(Action::Restart),
+            b.label(Action::MoveUp),
+            b.label(Action::MoveDown),
+            b.label(Action::Fullscreen)
+        )
+    };
+    let hd = measure_text(&hint, None, 20, 1.0);
+    draw_text(&hint, (sw - hd.width) * 0.5, sh - 40.0, 20.0, DARKGRAY);
 
     draw_vignette(sw, sh, settings.vignette, 0.0, false);
 }
 
-enum MainMenuAction { Start, Settings, Quit }
-
-fn update_main_menu(selected: &mut usize, mode: &mut GameMode) -> Option<MainMenuAction> {
-    let count = 4usize;
-    if is_key_pressed(KeyCode::Up) {
+enum MainMenuAction { Start, WatchReplay, Leaderboard, Settings, Quit }
+
+fn update_main_menu(
+    nav: MenuInput,
+    selected: &mut usize,
+    mode: &mut usize,
+    modes: usize,
+    seed: &mut Option<u64>,
+    last_seed: Option<u64>,
+) -> Option<MainMenuAction> {
+    let count = 7usize;
+    if nav.up {
         if *selected == 0 { *selected = count - 1; } else { *selected -= 1; }
     }
-    if is_key_pressed(KeyCode::Down) {
+    if nav.down {
         *selected = (*selected + 1) % count;
     }
-    if is_key_pressed(KeyCode::Left) {
-        if *selected == 1 {
-            let idx = (mode.index() + 2) % 3; // prev
-            *mode = GameMode::from_index(idx);
+    if nav.left && *selected == 1 {
+        *mode = (*mode + modes - 1) % modes; // prev
+    }
+    if nav.right && *selected == 1 {
+        *mode = (*mode + 1) % modes; // next
+    }
+    // Always drain typed chars so stale ones don't land in the seed later
+    while let Some(c) = get_char_pressed() {
+        if *selected == 2 {
+            if let Some(d) = c.to_digit(10) {
+                let cur = seed.unwrap_or(0);
+                // Capped to what TOML (and so the leaderboard file) can store
+                let next = cur.checked_mul(10).and_then(|v| v.checked_add(d as u64));
+                if let Some(next) = next.filter(|&v| v <= i64::MAX as u64) {
+                    *seed = Some(next);
+                }
+            }
         }
     }
-    if is_key_pressed(KeyCode::Right) {
-        if *selected == 1 {
-            let idx = (mode.index() + 1) % 3; // next
-            *mode = GameMode::from_index(idx);
+    if *selected == 2 {
+        if nav.left || nav.right {
+            *seed = if seed.is_some() { None } else { last_seed };
+        }
+        if is_key_pressed(KeyCode::Backspace) {
+            *seed = seed.map(|s| s / 10).filter(|&s| s > 0);
         }
     }
-    if is_key_pressed(KeyCode::Enter) {
+    if nav.confirm {
         return Some(match *selected {
             0 => MainMenuAction::Start,
-            1 => return None,
-            2 => MainMenuAction::Settings,
-            3 => MainMenuAction::Quit,
+            1 | 2 => return None,
+            3 => MainMenuAction::WatchReplay,
+            4 => MainMenuAction::Leaderboard,
+            5 => MainMenuAction::Settings,
+            6 => MainMenuAction::Quit,
             _ => return None,
         });
     }
     None
 }
 
-fn draw_settings_menu(selected: usize, s: &Settings) {
+/// Drawn over the frozen arena.
+fn draw_pause_menu(selected: usize, bindings: &Bindings) {
+    let sw = screen_width();
+    let sh = screen_height();
+    draw_rectangle(0.0, 0.0, sw, sh, Color::new(0.0, 0.0, 0.0, 0.6));
+    let title = "Paused";
+    let td = measure_text(title, None, 56, 1.0);
+    draw_text(title, (sw - td.width) * 0.5, sh * 0.5 - 90.0, 56.0, WHITE);
+
+    let items = ["Resume", "Restart", "Settings", "Quit to Menu"];
+    let mut y = sh * 0.5 - 20.0;
+    for (i, txt) in items.iter().enumerate() {
+        let c = if i == selected { Color::new(0.9, 0.9, 1.0, 1.0) } else { LIGHTGRAY };
+        let size = if i == selected { 30.0 } else { 26.0 };
+        let md = measure_text(txt, None, size as u16, 1.0);
+        draw_text(txt, (sw - md.width) * 0.5, y, size, c);
+        y += 34.0;
+    }
+
+    let b = bindings;
+    let hint = format!(
+        "{}: Select  |  {}/{}: Navigate  |  {}: Resume",
+        b.label(Action::Restart),
+        b.label(Action::MoveUp),
+        b.label(Action::MoveDown),
+        b.label(Action::Menu)
+    );
+    let hd = measure_text(&hint, None, 20, 1.0);
+    draw_text(&hint, (sw - hd.width) * 0.5, sh - 40.0, 20.0, GRAY);
+}
+
+enum PauseAction { Resume, Restart, Settings, Quit }
+
+fn update_pause_menu(nav: MenuInput, selected: &mut usize) -> Option<PauseAction> {
+    let count = 4usize;
+    if nav.up {
+        if *selected == 0 { *selected = count - 1; } else { *selected -= 1; }
+    }
+    if nav.down {
+        *selected = (*selected + 1) % count;
+    }
+    if nav.back {
+        return Some(PauseAction::Resume);
+    }
+    if nav.confirm {
+        return Some(match *selected {
+            0 => PauseAction::Resume,
+            1 => PauseAction::Restart,
+            2 => PauseAction::Settings,
+            _ => PauseAction::Quit,
+        });
+    }
+    None
+}
+
+// Settings menu rows, top to bottom; `draw_settings_menu` lists them in order
+const ROW_AUDIO: usize = 0;
+const ROW_VOLUME: usize = 1;
+const ROW_SHAKE: usize = 2;
+const ROW_VIGNETTE: usize = 3;
+const ROW_FULLSCREEN: usize = 4;
+const ROW_STEERING: usize = 5;
+const ROW_TICK_RATE: usize = 6;
+const ROW_CONTROLS: usize = 7;
+const ROW_BACK: usize = 8;
+const SETTINGS_ROWS: usize = 9;
+
+fn draw_settings_menu(selected: usize, s: &Settings, bindings: &Bindings) {
     let sw = screen_width();
     let sh = screen_height();
     clear_background(BLACK);
@@ -1023,12 +1465,21 @@ fn draw_settings_menu(selected: usize, s: &Settings) {
     let td = measure_text(title, None, 56, 1.0);
     draw_text(title, (sw - td.width) * 0.5, 110.0, 56.0, WHITE);
 
-    let items = [
+    let items: [String; SETTINGS_ROWS] = [
         format!("Audio: {}", if s.audio_enabled { "On" } else { "Off" }),
         format!("Volume: {:.0}%", (s.master_volume * 100.0).round()),
         format!("Shake: {}", if s.shake_enabled { "On" } else { "Off" }),
         format!("Vignette: {:.0}%", (s.vignette * 100.0).round()),
         format!("Fullscreen: {}", if s.fullscreen { "On" } else { "Off" }),
+        format!(
+            "Steering: {}",
+            match s.control_scheme {
+                ControlScheme::Keys => "Keys / Pad",
+                ControlScheme::Pointer => "Follow Mouse / Touch",
+            }
+        ),
+        format!("Tick Rate: {} Hz", s.tick_rate),
+        "Controls".to_string(),
         "Back".to_string(),
     ];
 
@@ -1041,50 +1492,233 @@ fn draw_settings_menu(selected: usize, s: &Settings) {
         y += 32.0;
     }
 
-    let hint = "Enter/Left/Right to change, Esc to back, F11 Fullscreen";
-    let hd = measure_text(hint, None, 20, 1.0);
-    draw_text(hint, (sw - hd.width) * 0.5, sh - 40.0, 20.0, DARKGRAY);
+    let b = bindings;
+    let hint = format!(
+        "{}/{}/{} to change, {} to back, {} Fullscreen",
+        b.label(Action::Restart),
+        b.label(Action::MoveLeft),
+        b.label(Action::MoveRight),
+        b.label(Action::Menu),
+        b.label(Action::Fullscreen)
+    );
+    let hd = measure_text(&hint, None, 20, 1.0);
+    draw_text(&hint, (sw - hd.width) * 0.5, sh - 40.0, 20.0, DARKGRAY);
 
     draw_vignette(sw, sh, s.vignette, 0.0, false);
 }
 
-fn update_settings_menu(selected: &mut usize, s: &mut Settings) -> bool {
-    let count = 6usize;
-    if is_key_pressed(KeyCode::Up) {
+enum SettingsAction { Controls, Back }
+
+fn update_settings_menu(
+    nav: MenuInput,
+    bindings: &Bindings,
+    selected: &mut usize,
+    s: &mut Settings,
+) -> Option<SettingsAction> {
+    let count = SETTINGS_ROWS;
+    if nav.up {
         if *selected == 0 { *selected = count - 1; } else { *selected -= 1; }
     }
-    if is_key_pressed(KeyCode::Down) {
+    if nav.down {
         *selected = (*selected + 1) % count;
     }
-    if is_key_pressed(KeyCode::Left) {
+    if nav.left {
         match *selected {
-            0 => s.audio_enabled = !s.audio_enabled,
-            1 => s.master_volume = (s.master_volume - 0.1).clamp(0.0, 1.0),
-            2 => s.shake_enabled = !s.shake_enabled,
-            3 => s.vignette = (s.vignette - 0.1).clamp(0.0, 1.0),
-            4 => { s.fullscreen = !s.fullscreen; set_fullscreen(s.fullscreen); },
+            ROW_AUDIO => s.audio_enabled = !s.audio_enabled,
+            ROW_VOLUME => s.master_volume = (s.master_volume - 0.1).clamp(0.0, 1.0),
+            ROW_SHAKE => s.shake_enabled = !s.shake_enabled,
+            ROW_VIGNETTE => s.vignette = (s.vignette - 0.1).clamp(0.0, 1.0),
+            ROW_FULLSCREEN => { s.fullscreen = !s.fullscreen; set_fullscreen(s.fullscreen); },
+            ROW_STEERING => s.control_scheme = s.control_scheme.toggled(),
+            ROW_TICK_RATE => s.tick_rate = cycle_tick_rate(s.tick_rate, TICK_RATES.len() - 1),
             _ => {}
         }
     }
-    if is_key_pressed(KeyCode::Right) {
+    if nav.right {
         match *selected {
-            0 => s.audio_enabled = !s.audio_enabled,
-            1 => s.master_volume = (s.master_volume + 0.1).clamp(0.0, 1.0),
-            2 => s.shake_enabled = !s.shake_enabled,
-            3 => s.vignette = (s.vignette + 0.1).clamp(0.0, 1.0),
-            4 => { s.fullscreen = !s.fullscreen; set_fullscreen(s.fullscreen); },
+            ROW_AUDIO => s.audio_enabled = !s.audio_enabled,
+            ROW_VOLUME => s.master_volume = (s.master_volume + 0.1).clamp(0.0, 1.0),
+            ROW_SHAKE => s.shake_enabled = !s.shake_enabled,
+            ROW_VIGNETTE => s.vignette = (s.vignette + 0.1).clamp(0.0, 1.0),
+            ROW_FULLSCREEN => { s.fullscreen = !s.fullscreen; set_fullscreen(s.fullscreen); },
+            ROW_STEERING => s.control_scheme = s.control_scheme.toggled(),
+            ROW_TICK_RATE => s.tick_rate = cycle_tick_rate(s.tick_rate, 1),
             _ => {}
         }
     }
-    if is_key_pressed(KeyCode::Enter) {
-        if *selected == 5 { return true; }
+    if nav.confirm && *selected == ROW_CONTROLS {
+        return Some(SettingsAction::Controls);
     }
-    if is_key_pressed(KeyCode::Escape) {
-        return true;
+    if nav.confirm && *selected == ROW_BACK || nav.back {
+        return Some(SettingsAction::Back);
     }
-    if is_key_pressed(KeyCode::F11) {
+    if bindings.pressed(Action::Fullscreen) {
         s.fullscreen = !s.fullscreen;
         set_fullscreen(s.fullscreen);
     }
-    false
+    None
+}
+
+/// The tick rate `by` places along `TICK_RATES`, wrapping around.
+fn cycle_tick_rate(hz: u32, by: usize) -> u32 {
+    let i = TICK_RATES.iter().position(|&r| r == hz).unwrap_or(0);
+    TICK_RATES[(i + by) % TICK_RATES.len()]
+}
+
+fn draw_controls(row: usize, slot: usize, capturing: bool, b: &Bindings, settings: &Settings) {
+    let sw = screen_width();
+    let sh = screen_height();
+    clear_background(BLACK);
+    let title = "Controls";
+    let td = measure_text(title, None, 56, 1.0);
+    draw_text(title, (sw - td.width) * 0.5, 100.0, 56.0, WHITE);
+
+    let conflicts = b.conflicts();
+    let x0 = (sw * 0.5 - 120.0 - 75.0 * SLOTS as f32).max(20.0);
+    let mut y = 170.0;
+    for (i, &a) in ACTIONS.iter().enumerate() {
+        let sel = i == row;
+        let c = if sel { Color::new(0.9, 0.9, 1.0, 1.0) } else { LIGHTGRAY };
+        draw_text(a.label(), x0, y, 24.0, c);
+        for s in 0..SLOTS {
+            let key = b.keys(a).get(s).copied();
+            let mut txt = key.map(controls::key_label).unwrap_or_else(|| "-".to_string());
+            if sel && s == slot {
+                txt = if capturing { "press a key...".to_string() } else { format!("[ {} ]", txt) };
+            }
+            let clash = key.is_some_and(|k| conflicts.iter().any(|&(ck, _, _)| ck == k));
+            let kc = if clash { Color::new(1.0, 0.45, 0.45, 1.0) } else { c };
+            draw_text(&txt, x0 + 260.0 + s as f32 * 150.0, y, 24.0, kc);
+        }
+        y += 30.0;
+    }
+    for (i, txt) in ["Reset to Defaults", "Back"].iter().enumerate() {
+        let sel = row == ACTIONS.len() + i;
+        let c = if sel { Color::new(0.9, 0.9, 1.0, 1.0) } else { LIGHTGRAY };
+        draw_text(txt, x0, y + 10.0, if sel { 26.0 } else { 24.0 }, c);
+        y += 30.0;
+    }
+
+    if let Some(&(k, a1, a2)) = conflicts.first() {
+        let w = format!(
+            "Conflict: {} is bound to both {} and {}",
+            controls::key_label(k),
+            a1.label(),
+            a2.label()
+        );
+        let wd = measure_text(&w, None, 22, 1.0);
+        draw_text(&w, (sw - wd.width) * 0.5, y + 30.0, 22.0, Color::new(1.0, 0.45, 0.45, 1.0));
+    }
+
+    let hint = if capturing {
+        "Press the new key  |  Backspace: Cancel".to_string()
+    } else {
+        format!(
+            "{}: Rebind  |  Backspace: Clear  |  {}/{}: Slot  |  {}: Back",
+            b.label(Action::Restart),
+            b.label(Action::MoveLeft),
+            b.label(Action::MoveRight),
+            b.label(Action::Menu)
+        )
+    };
+    let hd = measure_text(&hint, None, 20, 1.0);
+    draw_text(&hint, (sw - hd.width) * 0.5, sh - 40.0, 20.0, DARKGRAY);
+
+    draw_vignette(sw, sh, settings.vignette, 0.0, false);
+}
+
+/// Returns true to leave the page.
+fn update_controls(
+    nav: MenuInput,
+    row: &mut usize,
+    slot: &mut usize,
+    capturing: &mut bool,
+    b: &mut Bindings,
+    notice: &mut Option<Notice>,
+) -> bool {
+    if *capturing {
+        let Some(key) = get_last_key_pressed() else {
+            // A pad can't supply a key, but it can back out
+            if nav.back {
+                *capturing = false;
+            }
+            return false;
+        };
+        *capturing = false;
+        if key == KeyCode::Backspace {
+            return false;
+        }
+        let a = ACTIONS[*row];
+        let name = controls::key_label(key);
+        *notice = match b.bind(a, *slot, key) {
+            Rebind::Bound => None,
+            Rebind::Swapped(other, old) => Some(Notice::info(format!(
+                "{} taken from {}, which now uses {}",
+                name,
+                other.label(),
+                controls::key_label(old)
+            ))),
+            Rebind::Moved(other) => {
+                Some(Notice::info(format!("{} moved from {}", name, other.label())))
+            }
+            Rebind::Refused(other) => Some(Notice::error(format!(
+                "{} is {}'s only key - rebind that first",
+                name,
+                other.label()
+            ))),
+            Rebind::Reserved => Some(Notice::error(format!("{} can't be rebound", name))),
+        };
+        return false;
+    }
+
+    let rows = ACTIONS.len() + 2;
+    if nav.up {
+        *row = (*row + rows - 1) % rows;
+    }
+    if nav.down {
+        *row = (*row + 1) % rows;
+    }
+    if nav.left || nav.right {
+        *slot = (*slot + 1) % SLOTS;
+    }
+    if *row < ACTIONS.len() {
+        if nav.confirm {
+            *capturing = true;
+        }
+        if is_key_pressed(KeyCode::Backspace) && !b.clear(ACTIONS[*row], *slot) {
+            *notice = Some(Notice::error("Every action needs at least one key".to_string()));
+        }
+    } else if nav.confirm && *row == ACTIONS.len() {
+        *b = Bindings::default();
+        *notice = Some(Notice::info("Controls reset to defaults".to_string()));
+    } else if nav.confirm {
+        return true;
+    }
+    nav.back
+}
+
+/// Arcade-style initials for pads: up/down cycles the last letter, right
+/// adds one, left removes one.
+fn edit_initials(initials: &mut String, nav: MenuInput) {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+    if nav.left {
+        initials.pop();
+        return;
+    }
+    if nav.right && initials.len() < INITIALS_LEN {
+        initials.push('A');
+        return;
+    }
+    let step = if nav.down {
+        1
+    } else if nav.up {
+        ALPHABET.len() - 1
+    } else {
+        return;
+    };
+    let next = initials
+        .pop()
+        .and_then(|c| ALPHABET.iter().position(|&a| a == c as u8))
+        .map_or(0, |i| (i + step) % ALPHABET.len());
+    initials.push(ALPHABET[next] as char);
 }
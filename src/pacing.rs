@@ -0,0 +1,128 @@
+// -------------------------------
+// Fixed-step pacing
+// -------------------------------
+// Frame time goes in, whole ticks come out. A hitch (window drag, alt-tab)
+// would otherwise queue up dozens of ticks for one frame, so catch-up is
+// capped and the rest of the stall is dropped: the game slows down for a
+// moment instead of skipping ahead unseen.
+const MAX_FRAME: f32 = 5.0 / 60.0; // longest frame time honored, in seconds
+const FRAME_SMOOTHING: f32 = 0.05; // weight of each new frame in the average
+
+pub struct Pacing {
+    dt: f32, // seconds per tick, from the world being stepped
+    acc: f32,
+    frame_ticks: u32,
+    avg_frame: f32, // smoothed frame time, seconds
+    expected: u32,  // ticks a typical frame runs at this rate
+    pub merged: u64,  // catch-up ticks: beyond what a typical frame runs
+    pub dropped: u64, // ticks thrown away by the cap
+}
+
+impl Pacing {
+    pub fn new(dt: f32) -> Self {
+        Self { dt, acc: 0.0, frame_ticks: 0, avg_frame: 0.0, expected: 1, merged: 0, dropped: 0 }
+    }
+
+    /// Starts a frame: `frame` seconds passed, played at `rate` (replay
+    /// speed or console timescale).
+    pub fn feed(&mut self, frame: f32, rate: f32) {
+        self.frame_ticks = 0;
+        if frame > MAX_FRAME {
+            self.dropped += ((frame - MAX_FRAME) * rate / self.dt) as u64;
+        }
+        let honored = frame.min(MAX_FRAME);
+        self.acc += honored * rate;
+        // Several ticks a frame is normal at high tick rates; only a frame
+        // that runs more than the usual count is catching up
+        self.avg_frame = if self.avg_frame > 0.0 {
+            self.avg_frame + (honored - self.avg_frame) * FRAME_SMOOTHING
+        } else {
+            honored
+        };
+        self.expected = ((self.avg_frame * rate / self.dt).ceil() as u32).max(1);
+    }
+
+    /// Takes one tick's worth of time if there is enough.
+    pub fn tick(&mut self) -> bool {
+        if self.acc < self.dt {
+            return false;
+        }
+        self.acc -= self.dt;
+        self.frame_ticks += 1;
+        if self.frame_ticks > self.expected {
+            self.merged += 1;
+        }
+        true
+    }
+
+    /// Discards leftover time, e.g. while a replay is paused.
+    pub fn reset(&mut self) {
+        self.acc = 0.0;
+    }
+
+    /// Seconds of simulated time not yet stepped.
+    pub fn lag(&self) -> f32 {
+        self.acc
+    }
+
+    /// How far the display is between the last tick and the next, 0..1.
+    pub fn alpha(&self) -> f32 {
+        (self.acc / self.dt).clamp(0.0, 1.0)
+    }
+
+    pub fn frame_ticks(&self) -> u32 {
+        self.frame_ticks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feeds `frames` frames of `frame` seconds, stepping each one dry.
+    fn run(p: &mut Pacing, frame: f32, frames: usize) -> u32 {
+        let mut ticks = 0;
+        for _ in 0..frames {
+            p.feed(frame, 1.0);
+            while p.tick() {
+                ticks += 1;
+                assert!((0.0..=1.0).contains(&p.alpha()), "alpha {}", p.alpha());
+            }
+            assert!((0.0..=1.0).contains(&p.alpha()), "alpha {}", p.alpha());
+        }
+        ticks
+    }
+
+    #[test]
+    fn a_hitch_is_capped_and_the_rest_dropped() {
+        let mut p = Pacing::new(1.0 / 60.0);
+        p.feed(1.0, 1.0);
+        assert!(p.lag() <= MAX_FRAME);
+        assert!((54..=55).contains(&p.dropped), "dropped {}", p.dropped);
+        let mut ticks = 0;
+        while p.tick() {
+            ticks += 1;
+        }
+        assert_eq!(ticks, 5);
+    }
+
+    #[test]
+    fn steady_frames_merge_nothing() {
+        for (hz, frame) in [(240.0, 1.0 / 60.0), (120.0, 1.0 / 60.0), (60.0, 1.0 / 144.0)] {
+            let mut p = Pacing::new(1.0 / hz);
+            let ticks = run(&mut p, frame, 600);
+            assert!((ticks as f32 - 600.0 * frame * hz).abs() <= 1.0, "{} ticks", ticks);
+            assert_eq!(p.merged, 0, "{} Hz at {}s frames", hz, frame);
+            assert_eq!(p.dropped, 0);
+        }
+    }
+
+    #[test]
+    fn catch_up_after_a_slow_frame_counts_as_merged() {
+        let mut p = Pacing::new(1.0 / 240.0);
+        run(&mut p, 1.0 / 60.0, 120);
+        run(&mut p, 3.0 / 60.0, 1);
+        assert!(p.merged >= 7, "merged {}", p.merged);
+        assert_eq!(p.dropped, 0);
+    }
+}
@@ -0,0 +1,59 @@
+use feedback_rush_core::Replay;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::save;
+
+// -------------------------------
+// Replay files on disk
+// -------------------------------
+const REPLAY_DIR: &str = "replays";
+const REPLAY_EXT: &str = "frr";
+const KEEP: usize = 20; // older files are deleted as new ones are saved
+
+/// Next to the save file, or the working directory if there is no home.
+fn replay_dir() -> PathBuf {
+    save::app_dir().unwrap_or_default().join(REPLAY_DIR)
+}
+
+/// Replay files in the directory, oldest first.
+fn replay_paths() -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(replay_dir()) else {
+        return Vec::new();
+    };
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.extension().is_some_and(|x| x == REPLAY_EXT))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// Writes the replay as `run-<unix seconds>.frr`, prunes all but the newest
+/// `KEEP` and returns its path.
+pub fn save(replay: &Replay) -> io::Result<PathBuf> {
+    let dir = replay_dir();
+    fs::create_dir_all(&dir)?;
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = dir.join(format!("run-{}.{}", secs, REPLAY_EXT));
+    fs::write(&path, replay.to_bytes())?;
+    let paths = replay_paths();
+    for old in &paths[..paths.len().saturating_sub(KEEP)] {
+        if let Err(e) = fs::remove_file(old) {
+            eprintln!("could not remove old replay {}: {}", old.display(), e);
+        }
+    }
+    Ok(path)
+}
+
+/// The most recent readable replay, if any.
+pub fn load_latest() -> Option<Replay> {
+    replay_paths()
+        .iter()
+        .rev()
+        .find_map(|p| fs::read(p).ok().and_then(|b| Replay::from_bytes(&b).ok()))
+}
@@ -0,0 +1,199 @@
+use feedback_rush_core::TICK_RATE;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::controls::Bindings;
+use crate::leaderboard::{Entry, Leaderboard};
+use crate::Settings;
+
+// -------------------------------
+// Persistent save data
+// -------------------------------
+const APP_DIR: &str = "feedback-rush";
+const SAVE_FILE: &str = "save.toml";
+
+/// Bump when the layout changes in a way `#[serde(default)]` can't absorb,
+/// and teach `migrate` how to bring older files forward.
+const SAVE_VERSION: u32 = 2;
+
+#[derive(Clone, Default)]
+pub struct SaveData {
+    pub settings: Settings,
+    pub bindings: Bindings,
+    pub leaderboard: Leaderboard,
+    pub initials: String, // last entered, to prefill the next prompt
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+struct SaveFile {
+    version: u32,
+    initials: String,
+    settings: Settings,
+    bindings: Bindings,
+    leaderboard: Leaderboard,
+    // v1 only: single best per mode id, folded into `leaderboard`
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    best_scores: BTreeMap<String, f32>,
+}
+
+impl Default for SaveFile {
+    fn default() -> Self {
+        Self {
+            version: SAVE_VERSION,
+            settings: Settings::default(),
+            bindings: Bindings::default(),
+            initials: String::new(),
+            leaderboard: Leaderboard::default(),
+            best_scores: BTreeMap::new(),
+        }
+    }
+}
+
+/// Per-user directory for saves and replays: `$XDG_CONFIG_HOME/feedback-rush`
+/// (or `~/.config/feedback-rush`) on Linux, the usual equivalents elsewhere.
+pub fn app_dir() -> Option<PathBuf> {
+    let env = |k: &str| std::env::var_os(k).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let base = if cfg!(target_os = "windows") {
+        env("APPDATA")
+    } else if cfg!(target_os = "macos") {
+        env("HOME").map(|h| h.join("Library/Application Support"))
+    } else {
+        env("XDG_CONFIG_HOME").or_else(|| env("HOME").map(|h| h.join(".config")))
+    };
+    base.map(|b| b.join(APP_DIR))
+}
+
+fn save_path() -> Option<PathBuf> {
+    app_dir().map(|d| d.join(SAVE_FILE))
+}
+
+/// Never fails: a missing file gives defaults, and an unreadable one is moved
+/// aside to `save.toml.bak` so the next write doesn't destroy it.
+pub fn load() -> SaveData {
+    let Some(path) = save_path() else {
+        return SaveData::default();
+    };
+    let Ok(text) = fs::read_to_string(&path) else {
+        return SaveData::default();
+    };
+    decode(&text).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        back_up(&path);
+        SaveData::default()
+    })
+}
+
+/// Parses and migrates a save file, or says why it can't be used.
+fn decode(text: &str) -> Result<SaveData, String> {
+    match toml::from_str::<SaveFile>(text) {
+        Ok(file) if file.version <= SAVE_VERSION => Ok(migrate(file).into()),
+        Ok(file) => {
+            Err(format!("save file version {} is newer than this build; ignoring it", file.version))
+        }
+        Err(e) => Err(format!("save file is corrupt ({}); starting fresh", e)),
+    }
+}
+
+pub fn store(data: &SaveData) -> io::Result<()> {
+    let Some(path) = save_path() else {
+        return Ok(());
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let text = toml::to_string_pretty(&SaveFile::from(data))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    // Write then rename so a crash mid-write can't leave a truncated save
+    let tmp = path.with_extension("toml.tmp");
+    fs::write(&tmp, text)?;
+    fs::rename(&tmp, &path)
+}
+
+fn migrate(mut file: SaveFile) -> SaveFile {
+    // Missing fields already fell back to their defaults through
+    // `#[serde(default)]`; only structural changes need work here.
+    if file.version < 2 {
+        // v1 kept one best score per mode: seed each table with it
+        for (mode, &score) in &file.best_scores {
+            if file.leaderboard.top(mode, TICK_RATE).is_empty() && score > 0.0 {
+                let entry = Entry { initials: "---".to_string(), score, ..Default::default() };
+                file.leaderboard.insert(mode, entry);
+            }
+        }
+        file.best_scores.clear();
+    }
+    file.version = SAVE_VERSION;
+    file
+}
+
+fn back_up(path: &Path) {
+    let _ = fs::rename(path, path.with_extension("toml.bak"));
+}
+
+impl From<SaveFile> for SaveData {
+    fn from(mut file: SaveFile) -> Self {
+        file.leaderboard.normalize();
+        SaveData {
+            settings: file.settings.sanitized(),
+            bindings: file.bindings,
+            leaderboard: file.leaderboard,
+            initials: file.initials,
+        }
+    }
+}
+
+impl From<&SaveData> for SaveFile {
+    fn from(data: &SaveData) -> Self {
+        SaveFile {
+            version: SAVE_VERSION,
+            settings: data.settings,
+            bindings: data.bindings.clone(),
+            initials: data.initials.clone(),
+            leaderboard: data.leaderboard.clone(),
+            best_scores: BTreeMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn v1_best_scores_seed_the_tables() {
+        let v1 = "version = 1\n[best_scores]\nclassic = 120.0\nnightmare = 0.0\n";
+        let data = decode(v1).unwrap();
+        let top = data.leaderboard.top("classic", TICK_RATE);
+        assert_eq!(top.len(), 1);
+        assert_eq!((top[0].initials.as_str(), top[0].score), ("---", 120.0));
+        assert!(data.leaderboard.top("nightmare", TICK_RATE).is_empty());
+    }
+
+    #[test]
+    fn round_trip_keeps_everything() {
+        let mut data = SaveData { initials: "ABC".to_string(), ..Default::default() };
+        data.settings.tick_rate = 240;
+        data.settings.vignette = 0.25;
+        let entry = Entry { score: 99.0, tick_rate: 240, ..Default::default() };
+        data.leaderboard.insert("classic", entry);
+        let text = toml::to_string_pretty(&SaveFile::from(&data)).unwrap();
+        let back = decode(&text).unwrap();
+        assert_eq!(back.initials, "ABC");
+        assert_eq!((back.settings.tick_rate, back.settings.vignette), (240, 0.25));
+        assert_eq!(back.leaderboard.best("classic", 240), 99.0);
+        assert!(back.leaderboard.top("classic", TICK_RATE).is_empty());
+    }
+
+    #[test]
+    fn bad_files_are_refused() {
+        assert!(decode("version = [").is_err());
+        assert!(decode(&format!("version = {}", SAVE_VERSION + 1)).is_err());
+        // Out-of-range settings are clamped rather than refused
+        let data = decode("[settings]\nvignette = 7.0\ntick_rate = 75\n").unwrap();
+        assert_eq!((data.settings.vignette, data.settings.tick_rate), (1.0, TICK_RATE));
+    }
+}
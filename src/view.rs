@@ -0,0 +1,34 @@
+use macroquad::prelude::*;
+
+// -------------------------------
+// Arena view
+// -------------------------------
+// The arena has a fixed logical size; this fits it to the window at a
+// uniform scale, centred, with black bars filling the rest.
+pub fn arena_camera(arena: Vec2) -> Camera2D {
+    let (sw, sh) = (screen_width(), screen_height());
+    let scale = (sw / arena.x).min(sh / arena.y);
+    Camera2D {
+        target: arena * 0.5,
+        zoom: vec2(2.0 * scale / sw, 2.0 * scale / sh),
+        ..Default::default()
+    }
+}
+
+/// A window position (mouse, touch) in arena coordinates.
+pub fn to_arena(arena: Vec2, screen: Vec2) -> Vec2 {
+    arena_camera(arena).screen_to_world(screen)
+}
+
+/// Covers whatever of the window lies outside the arena. Call with the
+/// default camera active.
+pub fn draw_letterbox(arena: Vec2) {
+    let cam = arena_camera(arena);
+    let tl = cam.world_to_screen(Vec2::ZERO);
+    let br = cam.world_to_screen(arena);
+    let (sw, sh) = (screen_width(), screen_height());
+    draw_rectangle(0.0, 0.0, sw, tl.y, BLACK);
+    draw_rectangle(0.0, br.y, sw, sh - br.y, BLACK);
+    draw_rectangle(0.0, 0.0, tl.x, sh, BLACK);
+    draw_rectangle(br.x, 0.0, sw - br.x, sh, BLACK);
+}